@@ -0,0 +1,62 @@
+/// What has happened in the battle so far, for moves and abilities that depend on it. See Analytic and Revenge.\
+/// The default is the first turn of a battle with nothing unusual going on.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct BattleContext {
+    /// Whether the attacker moves after the target this turn. See Analytic and Payback.
+    pub attacker_moves_last: bool,
+    /// Whether the target switched in this turn. See Stakeout.
+    pub target_switched_in: bool,
+    /// How many of the attacker's allies have fainted. See Supreme Overlord.
+    pub allies_fainted: u8,
+    /// Whether the target hit the attacker earlier this turn. See Revenge and Avalanche.
+    pub attacker_was_hit: bool,
+    /// Whether the attacker is still holding an item. `None` uses the attacker's item. See Acrobatics.
+    pub attacker_has_item: Option<bool>,
+    /// Whether the target is still holding an item. `None` uses the defender's item. See Knock Off.
+    pub defender_has_item: Option<bool>,
+    /// How many times in a row the attacker has already used this move. See Fury Cutter and the Metronome item.
+    pub consecutive_uses: u8,
+    /// The base Attack of each party member that joins in Beat Up. Empty slots are fainted, statused or missing.\
+    /// If every slot is empty, only the attacker joins in.
+    pub party_base_attack: [Option<u8>; 6],
+}
+impl BattleContext {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl BattleContext {
+    pub fn attacker_moves_last(mut self, moves_last: bool) -> Self {
+        self.attacker_moves_last = moves_last;
+        self
+    }
+    pub fn target_switched_in(mut self, switched_in: bool) -> Self {
+        self.target_switched_in = switched_in;
+        self
+    }
+    pub fn allies_fainted(mut self, fainted: u8) -> Self {
+        self.allies_fainted = fainted;
+        self
+    }
+    pub fn attacker_was_hit(mut self, was_hit: bool) -> Self {
+        self.attacker_was_hit = was_hit;
+        self
+    }
+    pub fn attacker_has_item(mut self, has_item: bool) -> Self {
+        self.attacker_has_item = Some(has_item);
+        self
+    }
+    pub fn defender_has_item(mut self, has_item: bool) -> Self {
+        self.defender_has_item = Some(has_item);
+        self
+    }
+    pub fn consecutive_uses(mut self, uses: u8) -> Self {
+        self.consecutive_uses = uses;
+        self
+    }
+    pub fn party_base_attack(mut self, party: [Option<u8>; 6]) -> Self {
+        self.party_base_attack = party;
+        self
+    }
+}
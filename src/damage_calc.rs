@@ -1,11 +1,18 @@
 use std::fmt::{Debug, Display};
+use std::ops::Add;
 
-use crate::moves::{Category, Flag, MoveData, OverrideOffensivePokemon};
-use crate::names::abilities::Ability;
-use crate::pokemon::Pokemon;
+use crate::battle_context::BattleContext;
+use crate::doubles::{Doubles, Slot};
+use crate::field::Field;
+use crate::moves::{Category, CritRatio, Flag, MoveData, OverrideOffensivePokemon, PseudoWeather, SideCondition, Status, Terrain, VolatileStatus, Weather};
+use crate::names::{abilities::Ability, moves::Move};
+use crate::pokemon::{apply_boost, Pokemon};
 use crate::species::Stat;
 use crate::dex::{Dex, DexError, Identifier};
-use crate::types::Type;
+use crate::generation::Generation;
+use crate::histogram::DamageHistogram;
+use crate::learnsets::Gender;
+use crate::types::{DamageRelation, Type};
 
 type CalcInt = u32;
 type CalcFloat = f32;
@@ -34,12 +41,59 @@ impl DamageRange {
     pub fn ceiled_multiply(&mut self, value:CalcFloat) {
         self.0 = self.0.map(|damage| (damage as CalcFloat * value).ceil() as CalcInt);
     }
+    /// How many of the 16 damage rolls take away at least `hp`.
+    pub fn ko_rolls(&self, hp: u16) -> usize {
+        self.0.iter().filter(|damage| **damage >= hp as CalcInt).count()
+    }
+    /// Each roll as a percentage of the target's max HP.
+    pub fn percent(&self, max_hp: u16) -> [CalcFloat; 16] {
+        self.0.map(|damage| damage as CalcFloat * 100.0 / max_hp as CalcFloat)
+    }
+    /// Each roll as a percentage of the target's max HP, rounded down to one decimal place like Showdown.
+    pub fn showdown_percent(&self, max_hp: u16) -> [CalcFloat; 16] {
+        self.0.map(|damage| (damage * 1000 / max_hp as CalcInt) as CalcFloat / 10.0)
+    }
+    /// Each roll as the number of pixels it takes off the target's 48 pixel HP bar, from full HP.
+    pub fn pixels(&self, max_hp: u16) -> [u8; 16] {
+        self.0.map(|damage| HP_BAR_PIXELS - hp_pixels((max_hp as CalcInt).saturating_sub(damage), max_hp))
+    }
+    pub fn mean(&self) -> CalcFloat {
+        self.0.iter().sum::<CalcInt>() as CalcFloat / 16.0
+    }
+    /// Rolls are in ascending order, so this is the mean of the middle two.
+    pub fn median(&self) -> CalcFloat {
+        (self.0[7] + self.0[8]) as CalcFloat / 2.0
+    }
+    pub fn variance(&self) -> CalcFloat {
+        let mean = self.mean();
+        self.0.iter().map(|damage| (*damage as CalcFloat - mean).powi(2)).sum::<CalcFloat>() / 16.0
+    }
+}
+
+/// The number of pixels in the in-game HP bar.
+pub const HP_BAR_PIXELS: u8 = 48;
+
+/// The pixels of the HP bar that are filled at the given HP. Any HP left shows at least one pixel.
+pub fn hp_pixels(hp: CalcInt, max_hp: u16) -> u8 {
+    match hp * HP_BAR_PIXELS as CalcInt / max_hp as CalcInt {
+        0 if hp > 0 => 1,
+        pixels => pixels.min(HP_BAR_PIXELS as CalcInt) as u8
+    }
 }
 impl Default for DamageRange {
     fn default() -> Self {
         Self::new()
     }
 }
+impl Add for DamageRange {
+    type Output = Self;
+    /// Adds the damage of each roll, as if both hits got the same roll.
+    fn add(self, other: Self) -> Self {
+        let mut rolls = self.0;
+        rolls.iter_mut().zip(other.0).for_each(|(damage, other)| *damage += other);
+        Self(rolls)
+    }
+}
 impl PartialEq<[CalcInt; 16]> for DamageRange {
     fn eq(&self, other: &[CalcInt; 16]) -> bool {
         self.0 == *other   
@@ -53,40 +107,334 @@ fn pokemon_round(value: CalcFloat) -> CalcFloat {
 
 const ZERO_DAMAGE: DamageRange = DamageRange([0;16]);
 
-fn damage_calc(dex: &Dex, attacker: &Pokemon, defender: &Pokemon, move_: &MoveData, doubles: bool) -> DamageRange {
+/// Volatile statuses that make a pokemon semi-invulnerable, so that only a few moves can hit it.
+const SEMI_INVULNERABLE: [VolatileStatus; 7] = [
+    VolatileStatus::Fly, VolatileStatus::Bounce, VolatileStatus::Dig, VolatileStatus::Dive,
+    VolatileStatus::PhantomForce, VolatileStatus::ShadowForce, VolatileStatus::SkyDrop
+];
+
+/// Moves that double their damage against a minimized target.
+const MINIMIZE_PUNISHERS: [Move; 9] = [
+    Move::Stomp, Move::BodySlam, Move::DragonRush, Move::HeatCrash, Move::HeavySlam,
+    Move::FlyingPress, Move::Steamroller, Move::MaliciousMoonsault, Move::SupercellSlam
+];
+
+/// The target can't be hit by this move, because of the given state. See Fly and Dynamax.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CannotHit(pub VolatileStatus);
+impl Display for CannotHit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "The target can't be hit during {:?}", self.0)
+    }
+}
+
+/// Moves whose type is decided by the move itself, so abilities like Normalize and Pixilate don't change it.
+const FIXED_TYPE_MOVES: [Move; 9] = [
+    Move::HiddenPower, Move::Judgment, Move::MultiAttack, Move::NaturalGift, Move::RevelationDance,
+    Move::Struggle, Move::TechnoBlast, Move::TerrainPulse, Move::WeatherBall
+];
+
+/// The weather, unless it is suppressed by Air Lock or Cloud Nine on the field or on either pokemon.
+fn active_weather(field: &Field, attacker: &Pokemon, defender: &Pokemon) -> Option<Weather> {
+    let suppressed = [attacker.ability, defender.ability].iter().any(|ability| [Ability::AirLock, Ability::CloudNine].contains(ability));
+    field.active_weather().filter(|_| !suppressed)
+}
+
+/// The base power and type of moves that depend on the user's state. `None` if the move fails. See Weather Ball.
+fn move_power_and_type(attacker: &Pokemon, defender: &Pokemon, move_: &MoveData, field: &Field, context: &BattleContext) -> Option<(CalcFloat, Type)> {
+    let item = attacker.active_item(field);
+    let mut power = move_.base_power as CalcFloat;
+    let mut type_ = move_.type_;
+    match move_.id() {
+        Some(Move::Judgment) => {
+            if let Some(plate) = item.filter(|item| item.z_move.is_none()).and_then(|item| item.on_plate) {type_ = plate}
+        },
+        Some(Move::MultiAttack) => {
+            if let Some(memory) = item.and_then(|item| item.on_memory) {type_ = memory}
+        },
+        Some(Move::TechnoBlast) => {
+            if let Some(drive) = item.and_then(|item| item.on_drive) {type_ = drive}
+        },
+        Some(Move::NaturalGift) => {
+            let natural_gift = item.filter(|item| item.is_berry)?.natural_gift?;
+            power = natural_gift.base_power as CalcFloat;
+            type_ = natural_gift.type_;
+        },
+        Some(Move::Fling) => {
+            power = item?.fling?.base_power as CalcFloat;
+        },
+        Some(Move::WeatherBall) => {
+            let umbrella = item.is_some_and(|item| item.id() == "utilityumbrella");
+            let weather_type = match active_weather(field, attacker, defender) {
+                Some(Weather::SunnyDay) if !umbrella => Some(Type::Fire),
+                Some(Weather::RainDance) if !umbrella => Some(Type::Water),
+                Some(Weather::Sandstorm) => Some(Type::Rock),
+                Some(Weather::Hail | Weather::Snow) => Some(Type::Ice),
+                _ => None
+            };
+            if let Some(weather_type) = weather_type {
+                type_ = weather_type;
+                power *= 2.0;
+            }
+        },
+        Some(Move::TerrainPulse) if attacker.is_grounded(field) => {
+            if let Some(terrain) = field.terrain {
+                type_ = match terrain {
+                    Terrain::Electric => Type::Electric,
+                    Terrain::Grassy => Type::Grass,
+                    Terrain::Misty => Type::Fairy,
+                    Terrain::Psychic => Type::Psychic,
+                };
+                power *= 2.0;
+            }
+        },
+        Some(Move::RevelationDance) => {
+            type_ = attacker.species.types[0];
+        },
+        Some(Move::AuraWheel) if attacker.species.name == "Morpeko-Hangry" => {
+            type_ = Type::Dark;
+        },
+        Some(Move::RagingBull) => {
+            type_ = match attacker.species.name.as_str() {
+                "Tauros-Paldea-Combat" => Type::Fighting,
+                "Tauros-Paldea-Blaze" => Type::Fire,
+                "Tauros-Paldea-Aqua" => Type::Water,
+                _ => type_
+            };
+        },
+        Some(Move::IvyCudgel) => {
+            type_ = match attacker.species.name.as_str() {
+                "Ogerpon-Wellspring" | "Ogerpon-Wellspring-Tera" => Type::Water,
+                "Ogerpon-Hearthflame" | "Ogerpon-Hearthflame-Tera" => Type::Fire,
+                "Ogerpon-Cornerstone" | "Ogerpon-Cornerstone-Tera" => Type::Rock,
+                _ => type_
+            };
+        },
+        Some(Move::TeraBlast) => {
+            if let Some(tera_type) = attacker.tera_type {type_ = tera_type}
+        },
+        Some(Move::HiddenPower) => {
+            type_ = attacker.hidden_power_type();
+            power = attacker.hidden_power_power() as CalcFloat;
+        },
+        Some(Move::Revenge | Move::Avalanche) if context.attacker_was_hit => power *= 2.0,
+        Some(Move::Payback) if context.attacker_moves_last => power *= 2.0,
+        Some(Move::Acrobatics) if !context.attacker_has_item.unwrap_or(attacker.item.is_some()) => power *= 2.0,
+        Some(Move::FuryCutter) => {
+            // It doubles from 10 power up to 160 in Gens 2-4, from 20 in Gen 5 and from 40 after that
+            let max_doublings = match attacker.gen {
+                Generation::One | Generation::Two | Generation::Three | Generation::Four => 4,
+                Generation::Five => 3,
+                _ => 2,
+            };
+            power = (power * (2.0 as CalcFloat).powi(context.consecutive_uses.min(max_doublings) as i32)).min(160.0);
+        },
+        Some(Move::EchoedVoice) => power = (power * (context.consecutive_uses.min(4) + 1) as CalcFloat).min(200.0),
+        Some(Move::Rollout | Move::IceBall) => power *= (2.0 as CalcFloat).powi((context.consecutive_uses % 5) as i32),
+        Some(Move::SpitUp) => {
+            if attacker.stockpile == 0 {return None}
+            power = 100.0 * attacker.stockpile as CalcFloat;
+        },
+        _ => {}
+    }
+    Some((power, type_))
+}
+
+/// The possible base powers of a move whose power is random, and their chances. A power of `None` doesn't deal damage.
+fn power_distribution(move_: &MoveData) -> Option<Vec<(CalcFloat, Option<CalcFloat>)>> {
+    let distribution = match move_.id()? {
+        Move::Magnitude => [(0.05, 10.0), (0.1, 30.0), (0.2, 50.0), (0.3, 70.0), (0.2, 90.0), (0.1, 110.0), (0.05, 150.0)]
+            .map(|(chance, power)| (chance, Some(power))).to_vec(),
+        // Present heals the target instead of damaging it 20% of the time
+        Move::Present => vec![(0.4, Some(40.0)), (0.3, Some(80.0)), (0.1, Some(120.0)), (0.2, None)],
+        Move::FickleBeam => vec![(0.7, Some(move_.base_power as CalcFloat)), (0.3, Some(move_.base_power as CalcFloat * 2.0))],
+        _ => return None
+    };
+    Some(distribution)
+}
+
+/// The base power of each of Beat Up's hits, from the Gen 5 onwards formula.
+fn beat_up_powers(attacker: &Pokemon, context: &BattleContext) -> Vec<CalcFloat> {
+    let party = context.party_base_attack.iter().flatten().copied().collect::<Vec<_>>();
+    let party = if party.is_empty() {vec![attacker.species.base_stats.attack]} else {party};
+    party.into_iter().map(|attack| (attack / 10 + 5) as CalcFloat).collect()
+}
+
+/// The chance of each critical hit stage landing a critical hit, from Gen 2 onwards.
+fn crit_chance_by_stage(gen: Generation) -> [CalcFloat; 5] {
+    match gen {
+        Generation::One | Generation::Two => [17.0/256.0, 1.0/8.0, 1.0/4.0, 85.0/256.0, 1.0/2.0],
+        Generation::Three | Generation::Four | Generation::Five => [1.0/16.0, 1.0/8.0, 1.0/4.0, 1.0/3.0, 1.0/2.0],
+        Generation::Six => [1.0/16.0, 1.0/8.0, 1.0/2.0, 1.0, 1.0],
+        _ => [1.0/24.0, 1.0/8.0, 1.0/2.0, 1.0, 1.0],
+    }
+}
+
+/// The chance that this move lands a critical hit.
+fn crit_chance(attacker: &Pokemon, defender: &Pokemon, move_: &MoveData, field: &Field) -> CalcFloat {
+    let ignore_defender_abilities = move_.ignore_ability || [Ability::MoldBreaker, Ability::Turboblaze, Ability::Teravolt].contains(&attacker.ability);
+    let blocked = [Ability::BattleArmor, Ability::ShellArmor].contains(&defender.ability) && !ignore_defender_abilities;
+    if blocked || field.defender_side.has_condition(SideCondition::LuckyChant) {
+        return 0.0
+    }
+    let merciless = attacker.ability == Ability::Merciless && matches!(defender.status, Some(Status::Poison | Status::Toxic));
+    if move_.will_crit || merciless || attacker.volatiles.contains(VolatileStatus::LaserFocus) {
+        return 1.0
+    }
+
+    let raised = match move_.crit_ratio {
+        CritRatio::Standard => 0,
+        CritRatio::Raised(stages) => stages
+    };
+    let focus_energy = attacker.volatiles.contains(VolatileStatus::FocusEnergy);
+    if attacker.gen == Generation::One {
+        // Based on speed, and Focus Energy quarters the chance instead of raising it.
+        let mut chance = attacker.species.base_stats.speed as u16 / 2;
+        if focus_energy {chance /= 4};
+        if raised > 0 {chance *= 8};
+        return chance.clamp(1, 255) as CalcFloat / 256.0
+    }
+
+    let mut stage = raised;
+    if focus_energy {stage += 2};
+    if attacker.volatiles.contains(VolatileStatus::DragonCheer) {
+        stage += if attacker.species.types.contains(&Type::Dragon) {2} else {1};
+    }
+    if attacker.ability == Ability::SuperLuck {stage += 1};
+    if let Some(item) = attacker.active_item(field) {
+        let used_by_holder = item.item_users.as_ref().is_some_and(|users| users.contains(&attacker.species.name));
+        stage += match item.id().as_str() {
+            "scopelens" | "razorclaw" => 1,
+            "leek" | "stick" | "luckypunch" if used_by_holder => 2,
+            _ => 0
+        };
+    }
+    crit_chance_by_stage(attacker.gen)[stage.min(4) as usize]
+}
+
+/// The stat used in damage calculation, before boosts. Wonder Room swaps defence and special defence.
+fn field_stat(pokemon: &Pokemon, stat: Stat, field: &Field) -> u16 {
+    let stat = match (stat, field.has_pseudo_weather(PseudoWeather::WonderRoom)) {
+        (Stat::Defence, true) => Stat::SpecialDefence,
+        (Stat::SpecialDefence, true) => Stat::Defence,
+        (stat, _) => stat,
+    };
+    pokemon.stat(stat)
+}
+
+/// The category a move is treated as. Some moves become physical if it would deal more damage.\
+/// Tera Blast only does this when the user is terastallized, which is when it has a tera type.
+fn move_category(attacker: &Pokemon, defender: &Pokemon, move_: &MoveData, field: &Field) -> Category {
+    let boosted_stat = |pokemon: &Pokemon, stat: Stat| apply_boost(field_stat(pokemon, stat, field), pokemon.boosts.get(stat)) as CalcFloat;
+    let physical = match move_.id() {
+        Some(Move::PhotonGeyser | Move::LightThatBurnstheSky) => {
+            boosted_stat(attacker, Stat::Attack) > boosted_stat(attacker, Stat::SpecialAttack)
+        },
+        Some(Move::TeraBlast) if attacker.tera_type.is_some() => {
+            boosted_stat(attacker, Stat::Attack) > boosted_stat(attacker, Stat::SpecialAttack)
+        },
+        Some(Move::ShellSideArm) => {
+            // Showdown picks randomly on a tie, this always picks special.
+            let base = (attacker.level as CalcFloat * 2.0 / 5.0 + 2.0).floor() * move_.base_power as CalcFloat;
+            let physical = ((base * boosted_stat(attacker, Stat::Attack)).floor() / boosted_stat(defender, Stat::Defence)).floor();
+            let special = ((base * boosted_stat(attacker, Stat::SpecialAttack)).floor() / boosted_stat(defender, Stat::SpecialDefence)).floor();
+            (physical / 50.0).floor() > (special / 50.0).floor()
+        },
+        _ => return move_.category
+    };
+    if physical {
+        Category::Physical
+    } else {
+        Category::Special
+    }
+}
+
+/// `power` replaces the move's base power. See [power_distribution].
+/// Multi-hit moves do the damage of every hit, as if each got the same roll.
+fn damage_calc(calc: &ReadyCalc, power: Option<CalcFloat>, crit: bool, doubles: bool) -> Result<DamageRange, CannotHit> {
+    let mut damage = hit_damage(calc, power, crit, doubles)?;
+    damage.pokerounded_multiply(calc.3.number_of_hits.max() as f32);
+    Ok(damage)
+}
+
+/// The damage of one hit of the move. See [damage_calc].
+fn hit_damage(calc: &ReadyCalc, power: Option<CalcFloat>, crit: bool, doubles: bool) -> Result<DamageRange, CannotHit> {
+    let &CalcBuilder(dex, ref attacker, ref defender, move_, ref field, ref context) = calc;
     if move_.category == Category::Status {
-        return ZERO_DAMAGE;
+        return Ok(ZERO_DAMAGE);
+    }
+
+    // --- Semi-invulnerability ---
+    if let Some(state) = SEMI_INVULNERABLE.into_iter().find(|state| defender.volatiles.contains(*state)) {
+        let hits = match (state, move_.id()) {
+            (VolatileStatus::Fly | VolatileStatus::Bounce | VolatileStatus::SkyDrop, Some(Move::Gust | Move::Twister | Move::SkyUppercut | Move::Thunder | Move::Hurricane | Move::SmackDown | Move::ThousandArrows)) => true,
+            (VolatileStatus::Dig, Some(Move::Earthquake | Move::Magnitude | Move::Fissure)) => true,
+            (VolatileStatus::Dive, Some(Move::Surf | Move::Whirlpool)) => true,
+            _ => [attacker.ability, defender.ability].contains(&Ability::NoGuard)
+        };
+        if !hits {
+            return Err(CannotHit(state))
+        }
+    }
+    let dynamaxed = defender.volatiles.contains(VolatileStatus::Dynamax);
+    if dynamaxed && matches!(move_.id(), Some(Move::LowKick | Move::GrassKnot | Move::HeavySlam | Move::HeatCrash)) {
+        return Err(CannotHit(VolatileStatus::Dynamax))
     }
 
     let ignore_defender_abilities = 
         move_.ignore_ability || 
         [Ability::MoldBreaker, Ability::Turboblaze, Ability::Teravolt].contains(&attacker.ability);
 
-    let attacker_stat_source = match move_.override_offensive_pokemon {
-        None => attacker,
-        Some(OverrideOffensivePokemon::Target) => defender
-    };
-
-    let (offence_stat, defence_stat) = match move_.category {
+    let category = move_category(attacker, defender, move_, field);
+    let (offence_stat, defence_stat) = match category {
         Category::Physical => (Stat::Attack, Stat::Defence),
         Category::Special => (Stat::SpecialAttack, Stat::SpecialDefence),
         Category::Status => panic!("Status moves exit early")
     };
-
-    let offence_stat = move_.override_defensive_stat.unwrap_or(offence_stat);
+    let offence_stat = move_.override_offensive_stat.unwrap_or(offence_stat);
     let defence_stat = move_.override_defensive_stat.unwrap_or(defence_stat);
 
-    let mut attack = attacker_stat_source.stat(offence_stat) as CalcFloat;
-    let mut defence = defender.stat(defence_stat) as CalcFloat;
-    let mut power = move_.base_power as CalcFloat;
+    // The boosts used always belong to the pokemon whose stat is used. See Foul Play and Body Press.
+    let offence_source = match move_.override_offensive_pokemon {
+        None => attacker,
+        Some(OverrideOffensivePokemon::Target) => defender
+    };
+    let mut offence_boost = offence_source.boosts.get(offence_stat);
+    let mut defence_boost = defender.boosts.get(defence_stat);
+    // Unaware only ignores the opponent's boosts, so it never ignores the target's boosts during Foul Play.
+    if move_.override_offensive_pokemon.is_none() && defender.ability == Ability::Unaware && !ignore_defender_abilities {
+        offence_boost = 0;
+    }
+    if move_.ignore_defensive || attacker.ability == Ability::Unaware {
+        defence_boost = 0;
+    }
+    // Gen 1 crits ignore every stat stage, later crits only the ones that would weaken them.
+    if crit && attacker.gen == Generation::One {
+        offence_boost = 0;
+        defence_boost = 0;
+    } else if crit {
+        offence_boost = offence_boost.max(0);
+        defence_boost = defence_boost.min(0);
+    }
+
+    let makes_contact = move_.has_flag(Flag::Contact) || (move_.id() == Some(Move::ShellSideArm) && category == Category::Physical);
+
+    let mut attack = apply_boost(field_stat(offence_source, offence_stat, field), offence_boost) as CalcFloat;
+    let defence = apply_boost(field_stat(defender, defence_stat, field), defence_boost) as CalcFloat;
     let mut other_modifications = 1.0;
 
     let multi_target = move_.target.is_multi_target();
 
-    let mut current_move_type = move_.type_;
+    // --- Move Specific Power and Type ---
+    let Some((move_power, mut current_move_type)) = move_power_and_type(attacker, defender, move_, field, context) else {
+        return Ok(ZERO_DAMAGE)
+    };
+    let mut power = power.unwrap_or(move_power);
+    let move_specific_power = power;
+    let fixed_type = move_.id().is_some_and(|id| FIXED_TYPE_MOVES.contains(&id));
 
     // --- Type Modifications ---
-    if Type::Normal == current_move_type {
+    if Type::Normal == current_move_type && !fixed_type {
         let (multiplier, alter_move_type) = match attacker.ability {
             Ability::Aerilate => {(1.2, Type::Flying)},
             Ability::Galvanize => {(1.2, Type::Electric)},
@@ -100,7 +448,7 @@ fn damage_calc(dex: &Dex, attacker: &Pokemon, defender: &Pokemon, move_: &MoveDa
     if move_.has_flag(Flag::Sound) && attacker.ability == Ability::LiquidVoice {
         current_move_type = Type::Water
     };
-    if attacker.ability == Ability::Normalize && move_.z_move.is_none() && !["hiddenpower", "weatherball", "naturalgift", "technoblast", "judgment", "multiattack", "terrainpulse"].contains(&move_.name.as_str()) {
+    if attacker.ability == Ability::Normalize && move_.z_move.is_none() && !fixed_type {
         current_move_type = Type::Normal;
     }
 
@@ -120,24 +468,55 @@ fn damage_calc(dex: &Dex, attacker: &Pokemon, defender: &Pokemon, move_: &MoveDa
 
     // --- Power Modifications ---
     // Note power modifications that also modify type are in Type Modifications
-    if attacker.ability == Ability::Technician && move_.base_power < 60 {power *= 1.5};
+    if attacker.ability == Ability::Technician && move_specific_power <= 60.0 {power *= 1.5};
     if attacker.ability == Ability::WaterBubble && current_move_type == Type::Water {power *= 1.5};
-    //TODO Analytic
+    if attacker.ability == Ability::Analytic && context.attacker_moves_last {power *= 1.3};
     if attacker.ability == Ability::IronFist && move_.has_flag(Flag::Punch) {power *= 1.2};
     if attacker.ability == Ability::MegaLauncher && move_.has_flag(Flag::Pulse) {power *= 1.5};
     if attacker.ability == Ability::PunkRock && move_.has_flag(Flag::Sound) {power *= 1.3};
     if attacker.ability == Ability::Reckless && (move_.has_crash_damage || move_.recoil.is_some()) {power *= 1.2}; // Mind blown and struggle recoil don't count
-    //TODO Rivalry
+    if attacker.ability == Ability::Rivalry {
+        power *= match (attacker.gender, defender.gender) {
+            (Some(Gender::Unknown), _) | (_, Some(Gender::Unknown)) | (None, _) | (_, None) => 1.0,
+            (Some(attacker_gender), Some(defender_gender)) if attacker_gender == defender_gender => 1.25,
+            _ => 0.75
+        }
+    };
     //TODO Sand Force
     if attacker.ability == Ability::Sharpness && move_.has_flag(Flag::Slicing) {power *= 1.5};
     if attacker.ability == Ability::SheerForce && move_.has_sheer_force {power *= 1.3};
-    //TODO Stakeout
+    if attacker.ability == Ability::Stakeout && context.target_switched_in {attack *= 2.0};
     if attacker.ability == Ability::SteelySpirit && current_move_type == Type::Steel {power *= 1.5};
     if attacker.ability == Ability::StrongJaw && move_.has_flag(Flag::Bite) {power *= 1.5};
-    //TODO Supreme Overlord
-    if attacker.ability == Ability::ToughClaws && move_.has_flag(Flag::Contact) {power *= 1.3};
+    if attacker.ability == Ability::SupremeOverlord {power *= 1.0 + 0.1 * context.allies_fainted.min(5) as CalcFloat};
+    if attacker.ability == Ability::ToughClaws && makes_contact {power *= 1.3};
     //TODO Toxic Boost
 
+    // --- Item Power Modifications ---
+    let ogerpon_mask = attacker.active_item(field).filter(|item| item.id().ends_with("mask"))
+        .and_then(|item| item.forced_forme.as_ref())
+        .is_some_and(|forme| attacker.species.name.starts_with(forme.as_str()));
+    if ogerpon_mask {power *= 1.2};
+
+    // --- Attacker and Target State ---
+    if attacker.volatiles.contains(VolatileStatus::Charge) && current_move_type == Type::Electric {power *= 2.0};
+    if dynamaxed && matches!(move_.id(), Some(Move::BehemothBlade | Move::BehemothBash | Move::DynamaxCannon)) {power *= 2.0};
+    let punished = match move_.id() {
+        Some(id) if defender.volatiles.contains(VolatileStatus::Minimize) && MINIMIZE_PUNISHERS.contains(&id) => true,
+        Some(Move::Earthquake | Move::Magnitude) => defender.volatiles.contains(VolatileStatus::Dig),
+        Some(Move::Surf | Move::Whirlpool) => defender.volatiles.contains(VolatileStatus::Dive),
+        Some(Move::Gust | Move::Twister) => [VolatileStatus::Fly, VolatileStatus::Bounce, VolatileStatus::SkyDrop].iter().any(|state| defender.volatiles.contains(*state)),
+        _ => false
+    };
+    if punished {other_modifications *= 2.0};
+    let knock_off_boost = move_.id() == Some(Move::KnockOff) && attacker.gen >= Generation::Six
+        && context.defender_has_item.unwrap_or(defender.item.is_some())
+        && defender.item.is_none_or(|item| item.can_be_removed_from(defender.species));
+    if knock_off_boost {power *= 1.5};
+    if attacker.active_item(field).is_some_and(|item| item.id() == "metronome") {
+        other_modifications *= 1.0 + 0.2 * context.consecutive_uses.min(5) as CalcFloat;
+    }
+
     // --- Ally Abilities ---
     //TODO Battery
     //TODO Power Spot
@@ -172,36 +551,35 @@ fn damage_calc(dex: &Dex, attacker: &Pokemon, defender: &Pokemon, move_: &MoveDa
 
     // --- Defender Abilities ---
     if !ignore_defender_abilities {
-        let immune = match (defender.ability, current_move_type) {
-            (Ability::EarthEater, Type::Ground) => true,
-            (Ability::FlashFire, Type::Fire) => true,
-            (Ability::DrySkin, Type::Water) => true,
-            (Ability::Levitate, Type::Ground) => true,
-            (Ability::LightningRod, Type::Electric) => true,
-            (Ability::MotorDrive, Type::Electric) => true,
-            (Ability::SapSipper, Type::Grass) => true,
-            (Ability::StormDrain, Type::Water) => true,
-            (Ability::VoltAbsorb, Type::Electric) => true,
-            (Ability::WaterAbsorb, Type::Water) => true,
-            (Ability::WellBakedBody, Type::Fire) => true,
-            _ => false
-        };
+        let immune = matches!((defender.ability, current_move_type),
+            (Ability::EarthEater, Type::Ground) |
+            (Ability::FlashFire, Type::Fire) |
+            (Ability::DrySkin, Type::Water) |
+            (Ability::Levitate, Type::Ground) |
+            (Ability::LightningRod, Type::Electric) |
+            (Ability::MotorDrive, Type::Electric) |
+            (Ability::SapSipper, Type::Grass) |
+            (Ability::StormDrain, Type::Water) |
+            (Ability::VoltAbsorb, Type::Electric) |
+            (Ability::WaterAbsorb, Type::Water) |
+            (Ability::WellBakedBody, Type::Fire)
+        );
         if immune {
-            return  ZERO_DAMAGE
+            return Ok(ZERO_DAMAGE)
         }
 
         //TODO Disguise
         if defender.ability == Ability::DrySkin && current_move_type == Type::Fire  {other_modifications *= 0.8};
         if defender.ability == Ability::Fluffy  && current_move_type == Type::Fire {other_modifications *= 0.5};
-        if defender.ability == Ability::Fluffy && move_.has_flag(Flag::Contact) {other_modifications *= 2.0};
+        if defender.ability == Ability::Fluffy && makes_contact {other_modifications *= 2.0};
         if defender.ability == Ability::Heatproof && current_move_type == Type::Fire {attack *= 0.5};
         //TODO Multiscale
         //TODO Shadow Shield (NOT INGORABLE)
-        if defender.ability == Ability::PunkRock && move_.has_flag(Flag::Contact) {other_modifications *= 2.0};
+        if defender.ability == Ability::PunkRock && makes_contact {other_modifications *= 2.0};
         if defender.ability == Ability::PurifyingSalt && current_move_type == Type::Ghost {attack *= 0.5};
         if defender.ability == Ability::ThickFat && (current_move_type == Type::Fire || current_move_type == Type::Ice) {attack *= 0.5};
         if defender.ability == Ability::WaterBubble && current_move_type == Type::Fire {other_modifications *= 2.0};
-        if defender.ability == Ability::IceScales && move_.category == Category::Special {other_modifications *= 2.0};
+        if defender.ability == Ability::IceScales && category == Category::Special {other_modifications *= 2.0};
     }
         
     let target_multiplier = match (doubles, multi_target) {
@@ -225,10 +603,11 @@ fn damage_calc(dex: &Dex, attacker: &Pokemon, defender: &Pokemon, move_: &MoveDa
         let type_ = dex.type_(type_).expect("Dex to handle all types");
         type_multiplier *= type_.damage_taken(current_move_type).to_multiplier();
     }
+    if defender.volatiles.contains(VolatileStatus::Tarshot) && current_move_type == Type::Fire {type_multiplier *= 2.0};
 
-    let n_hits = move_.number_of_hits.max();
-
-    let level = attacker.level as CalcFloat;
+    // Gen 1 crits double the attacker's level instead of multiplying the damage
+    let gen_one_crit = crit && attacker.gen == Generation::One;
+    let level = attacker.level as CalcFloat * if gen_one_crit {2.0} else {1.0};
     let attack = pokemon_round(attack);
     let power = pokemon_round(power);
     let defence = pokemon_round(defence);
@@ -237,8 +616,11 @@ fn damage_calc(dex: &Dex, attacker: &Pokemon, defender: &Pokemon, move_: &MoveDa
     damage = pokemon_round(damage * target_multiplier);
     // Parental Bond
     // Weather
-    // Glaive Rush
-    if move_.will_crit {damage = (damage * 1.5).floor()};
+    if defender.volatiles.contains(VolatileStatus::GlaiveRush) {damage = pokemon_round(damage * 2.0)};
+    if crit && !gen_one_crit {
+        let crit_multiplier = if attacker.gen >= Generation::Six {1.5} else {2.0};
+        damage = (damage * crit_multiplier).floor();
+    }
     let mut random = DamageRange::new();
     random.floored_multiply(damage/100.0); // From showdown, diverges from bulbapedia
     let mut damage = random;
@@ -248,8 +630,7 @@ fn damage_calc(dex: &Dex, attacker: &Pokemon, defender: &Pokemon, move_: &MoveDa
     damage.pokerounded_multiply(other_modifications);
     // ZMOVE
     // TERA SHIELD
-    damage.pokerounded_multiply(n_hits as f32);
-    damage
+    Ok(damage)
 }
 
 
@@ -262,7 +643,7 @@ impl MaybeAMove for &MoveData {}
 impl MaybeAMove for () {}
 
 #[derive(Clone, Copy)]
-pub struct CalcBuilder<'a, Attacker: MaybeAPokemon, Defender: MaybeAPokemon, Move: MaybeAMove>(&'a Dex, Attacker, Defender, Move);
+pub struct CalcBuilder<'a, Attacker: MaybeAPokemon, Defender: MaybeAPokemon, Move: MaybeAMove>(&'a Dex, Attacker, Defender, Move, Field, BattleContext);
 type ReadyCalc<'a> = CalcBuilder<'a, Pokemon<'a>, Pokemon<'a>, &'a MoveData>;
 
 impl Display for ReadyCalc<'_> {
@@ -271,50 +652,211 @@ impl Display for ReadyCalc<'_> {
     }
 }
 
-pub struct CalcOutcome<'a>(DamageRange, ReadyCalc<'a>);
+/// One possible base power of a move, and the damage it does. See Magnitude.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PowerOutcome {
+    pub chance: f32,
+    pub damage: DamageRange,
+    pub crit_damage: DamageRange,
+    /// The HP the target restores instead of taking damage. See Present.
+    pub heal: u16,
+}
+
+/// The damage for each possible base power, the calc, and the chance of a critical hit.
+pub struct CalcOutcome<'a>(Result<Vec<PowerOutcome>, CannotHit>, ReadyCalc<'a>, CalcFloat);
 impl CalcOutcome<'_> {
+    /// The most likely base power's outcome. See [Self::power_outcomes] for moves with random power.
+    fn likeliest(&self) -> Option<&PowerOutcome> {
+        self.power_outcomes().iter().reduce(|likeliest, outcome| if outcome.chance > likeliest.chance {outcome} else {likeliest})
+    }
+    /// Every possible base power's outcome, which is empty if the move can't hit the target.
+    pub fn power_outcomes(&self) -> &[PowerOutcome] {
+        self.0.as_deref().unwrap_or(&[])
+    }
+    /// The damage dealt without a critical hit, which is 0 if the move can't hit the target.\
+    /// Moves that always crit use the critical hit damage. Moves with random power use their most likely power.
     pub fn damage_range(&self) -> DamageRange {
-        self.0
+        self.likeliest().map_or(ZERO_DAMAGE, |outcome| outcome.damage)
+    }
+    /// The damage dealt with a critical hit.
+    pub fn crit_damage_range(&self) -> DamageRange {
+        self.likeliest().map_or(ZERO_DAMAGE, |outcome| outcome.crit_damage)
     }
-    pub fn calc_details(&self) -> &ReadyCalc {
+    pub fn crit_chance(&self) -> f32 {
+        self.2
+    }
+    /// Why the move can't hit the target, if it can't. See Dig and Phantom Force.
+    pub fn cannot_hit(&self) -> Option<CannotHit> {
+        self.0.as_ref().err().copied()
+    }
+    pub fn calc_details(&self) -> &ReadyCalc<'_> {
         &self.1
     }
+    /// The chance that this attack knocks out a defender on the given HP,
+    /// including the chance of a critical hit and the chance of each base power.
+    pub fn ko_chance(&self, hp: u16) -> f32 {
+        self.histogram().chance_at_least(hp as u32) as f32
+    }
+    /// The chance of each damage, including the chance of a critical hit and the chance of each base power.
+    pub fn histogram(&self) -> DamageHistogram {
+        DamageHistogram::from(self)
+    }
+    /// The chance that this attack knocks out the defender after it switches in at full HP to the defender's side of the field.
+    pub fn ko_chance_after_hazards(&self) -> Result<f32, DexError> {
+        let CalcBuilder(dex, _, defender, _, field, _) = &self.1;
+        let switch_in = defender.switch_in(dex, field, &field.defender_side)?;
+        Ok(self.ko_chance(switch_in.hp_after(defender.max_hp())))
+    }
+}
+
+/// Types whose moves were special before the physical/special split in Gen 4.
+const SPECIAL_TYPES: [Type; 8] = [Type::Fire, Type::Water, Type::Grass, Type::Electric, Type::Ice, Type::Psychic, Type::Dragon, Type::Dark];
+
+impl<'a> CalcOutcome<'a> {
+    /// The damage the defender deals back to the attacker with Counter, Mirror Coat, Metal Burst, Comeuppance or Bide
+    /// after taking this hit, for each damage roll. Multi-hit moves are only retaliated against for their last hit.\
+    /// Rolls that knock the defender out give 0, as do moves that can't retaliate against this hit.
+    pub fn retaliation<Id: Identifier>(&self, move_: Id) -> Result<DamageRange, DexError> {
+        let CalcBuilder(dex, attacker, defender, incoming, field, context) = &self.1;
+        let move_ = dex.move_(move_)?;
+        let Some((_, incoming_type)) = move_power_and_type(attacker, defender, incoming, field, context) else {
+            return Ok(ZERO_DAMAGE)
+        };
+        let incoming_category = match attacker.gen {
+            Generation::One | Generation::Two | Generation::Three if incoming.category != Category::Status => {
+                if SPECIAL_TYPES.contains(&incoming_type) {Category::Special} else {Category::Physical}
+            },
+            _ => move_category(attacker, defender, incoming, field)
+        };
+
+        let (allowed, multiplier) = match move_.id() {
+            Some(Move::Counter) if attacker.gen == Generation::One => ([Type::Normal, Type::Fighting].contains(&incoming_type), 2.0),
+            Some(Move::Counter) => (incoming_category == Category::Physical, 2.0),
+            Some(Move::MirrorCoat) => (incoming_category == Category::Special, 2.0),
+            Some(Move::Bide) => (incoming_category != Category::Status, 2.0),
+            Some(Move::MetalBurst | Move::Comeuppance) => (incoming_category != Category::Status, 1.5),
+            _ => (false, 0.0)
+        };
+        if !allowed {
+            return Ok(ZERO_DAMAGE)
+        }
+
+        // Bide ignores type immunities. Scrappy and Mind's Eye let Counter hit Ghost types.
+        let scrappy = [Ability::Scrappy, Ability::MindsEye].contains(&defender.ability) && [Type::Normal, Type::Fighting].contains(&move_.type_);
+        if move_.id() != Some(Move::Bide) && !scrappy {
+            for type_ in attacker.species.types.iter() {
+                if dex.type_(type_)?.damage_taken(move_.type_) == DamageRelation::Immune {
+                    return Ok(ZERO_DAMAGE)
+                }
+            }
+        }
+
+        // Only the last hit of a multi-hit move is retaliated against. Each of Beat Up's hits has its own power.
+        let total = self.damage_range();
+        let crit = self.2 >= 1.0;
+        let last_hit = match incoming.id() {
+            Some(Move::BeatUp) => hit_damage(&self.1, beat_up_powers(attacker, context).last().copied(), crit, false).unwrap_or(ZERO_DAMAGE),
+            _ if incoming.number_of_hits.max() > 1 => hit_damage(&self.1, None, crit, false).unwrap_or(ZERO_DAMAGE),
+            _ => total,
+        };
+
+        let hp = defender.hp() as CalcInt;
+        let mut rolls = last_hit.0;
+        rolls.iter_mut().zip(total.0).for_each(|(damage, total)| {
+            *damage = if total >= hp {0} else {(*damage as CalcFloat * multiplier).floor() as CalcInt}
+        });
+        Ok(DamageRange(rolls))
+    }
 }
 
 impl Dex {
-    pub fn calc(&self) -> CalcBuilder<(), (), ()> {
-        CalcBuilder(self, (), (), ())
+    pub fn calc(&self) -> CalcBuilder<'_, (), (), ()> {
+        CalcBuilder(self, (), (), (), Field::default(), BattleContext::default())
+    }
+}
+impl<A: MaybeAPokemon, D: MaybeAPokemon, M: MaybeAMove> CalcBuilder<'_, A, D, M> {
+    pub fn field(mut self, field: Field) -> Self {
+        self.4 = field;
+        self
+    }
+    pub fn context(mut self, context: BattleContext) -> Self {
+        self.5 = context;
+        self
     }
 }
 impl<'a, D: MaybeAPokemon, M: MaybeAMove> CalcBuilder<'a, (), D, M> {
     pub fn attacker(self, attacker: Pokemon<'a>) -> CalcBuilder<'a, Pokemon<'a>, D, M> {
-        CalcBuilder(self.0, attacker, self.2, self.3)
+        CalcBuilder(self.0, attacker, self.2, self.3, self.4, self.5)
     }
 }
 impl<'a, A: MaybeAPokemon, B: MaybeAMove> CalcBuilder<'a, A, (), B> {
     pub fn defender(self, defender: Pokemon<'a>) -> CalcBuilder<'a, A, Pokemon<'a>, B> {
-        CalcBuilder(self.0, self.1, defender, self.3)
+        CalcBuilder(self.0, self.1, defender, self.3, self.4, self.5)
     }
 }
 impl<'a, A: MaybeAPokemon, D: MaybeAPokemon> CalcBuilder<'a, A, D, ()> {
     pub fn move_<Id: Identifier>(self, move_:Id) -> Result<CalcBuilder<'a, A, D, &'a MoveData>, DexError> {
         let move_ = self.0.move_(move_)?;
-        Ok(CalcBuilder(self.0, self.1, self.2, move_))
+        Ok(CalcBuilder(self.0, self.1, self.2, move_, self.4, self.5))
     }
 }
 impl<'a> CalcBuilder<'a, Pokemon<'a>, Pokemon<'a>, ()> {
+    /// A calc for every move the attacker can learn, including as a prevolution.
     pub fn all_possible_attacks(self) -> Result<Vec<CalcBuilder<'a, Pokemon<'a>, Pokemon<'a>, &'a MoveData>>, DexError> {
-        let learnset = self.0.learnset(&self.1.species.name)?;
+        let learnset = self.0.resolved_learnset(self.1.species);
+        if learnset.is_empty() {
+            return Err(DexError::NotFound(self.1.species.name.clone()))
+        }
         Ok(learnset.all_moves().into_iter()
             .flat_map(|move_| self.move_(*move_))
             .collect()
         )  
     }
 }
+impl<'a> CalcBuilder<'a, Pokemon<'a>, (), &'a MoveData> {
+    /// Calcs this move against each pokemon it hits in a double battle, after redirection.
+    /// The attacker should be the pokemon in the `user` slot.\
+    /// Spread moves only get the spread reduction when they hit more than one pokemon. See Earthquake, which also hits the user's ally.
+    pub fn doubles(self, doubles: &Doubles<'a>, user: Slot, target: Slot) -> Vec<(Slot, CalcOutcome<'a>)> {
+        let targets = doubles.targets(user, target, self.3, &self.4);
+        let spread = targets.len() > 1;
+        targets.into_iter()
+            .filter_map(|slot| {
+                let mut field = self.4;
+                if slot.0 == user.0 {field.defender_side = field.attacker_side};
+                Some((slot, self.defender(*doubles.get(slot)?).field(field).calc(spread)))
+            })
+            .collect()
+    }
+}
 impl<'a> ReadyCalc<'a>{
     pub fn calc(self, doubles: bool) -> CalcOutcome<'a> {
-        let result = damage_calc(self.0, &self.1, &self.2, self.3, doubles);
-        CalcOutcome(result, self)
+        let crit_chance = crit_chance(&self.1, &self.2, self.3, &self.4);
+        let damage = |power: Option<CalcFloat>, crit: bool| -> Result<DamageRange, CannotHit> {
+            if self.3.id() == Some(Move::BeatUp) {
+                // Each party member's hit has its own power
+                return beat_up_powers(&self.1, &self.5).into_iter()
+                    .map(|power| damage_calc(&self, Some(power), crit, doubles))
+                    .try_fold(ZERO_DAMAGE, |total, hit| Ok(total + hit?))
+            }
+            damage_calc(&self, power, crit, doubles)
+        };
+        let outcome = |chance: CalcFloat, power: Option<CalcFloat>| -> Result<PowerOutcome, CannotHit> {
+            Ok(PowerOutcome {
+                chance,
+                damage: damage(power, crit_chance >= 1.0)?,
+                crit_damage: damage(power, crit_chance > 0.0)?,
+                heal: 0,
+            })
+        };
+        let result = match power_distribution(self.3) {
+            None => outcome(1.0, None).map(|outcome| vec![outcome]),
+            Some(distribution) => distribution.into_iter().map(|(chance, power)| match power {
+                Some(power) => outcome(chance, Some(power)),
+                None => Ok(PowerOutcome { chance, damage: ZERO_DAMAGE, crit_damage: ZERO_DAMAGE, heal: self.2.max_hp() / 4 }),
+            }).collect()
+        };
+        CalcOutcome(result, self, crit_chance)
     }
 }
 
@@ -324,13 +866,13 @@ impl<'a> ReadyCalc<'a>{
 mod tests {
     use std::fmt::Debug;
 
-    use crate::{dex::{Dex, DexError}, natures::Nature, species::Stat};
+    use crate::{battle_context::BattleContext, dex::{Dex, DexError}, field::Field, generation::Generation, learnsets::Gender, moves::{PseudoWeather, Terrain, VolatileStatus, Weather}, names::abilities::Ability, natures::Nature, species::Stat, types::Type};
 
-    use super::{CalcOutcome, DamageRange};
+    use super::{CalcOutcome, CannotHit, DamageRange};
 
     impl CalcOutcome<'_> {
         pub fn assert<T>(&self, value: T) where DamageRange: PartialEq<T>, T: Debug {
-            assert_eq!(self.0, value, "{}", self.1)
+            assert_eq!(self.damage_range(), value, "{}", self.1)
         }
     }
 
@@ -360,7 +902,276 @@ mod tests {
         let swampert = dex.pokemon("swampert")?
             .ev(Stat::Attack, 252);
         dex.calc().attacker(swampert).defender(punching_bag).move_("wickedblow")?.calc(true).assert([68, 69, 70, 71, 72, 72, 73, 74, 75, 76, 76, 77, 78, 79, 80, 81]);
-        
+
+        // Rivalry is stronger against the same gender and weaker against the other one
+        let nidoking = dex.pokemon("nidoking")?.ability(Ability::Rivalry).gender(Gender::Male);
+        let earth_power = dex.calc().attacker(nidoking).move_("earthpower")?;
+        let genderless = earth_power.defender(punching_bag).calc(false).damage_range();
+        assert!(earth_power.defender(punching_bag.gender(Gender::Male)).calc(false).damage_range().min() > genderless.max());
+        assert!(earth_power.defender(punching_bag.gender(Gender::Female)).calc(false).damage_range().max() < genderless.min());
+
+        Ok(())
+    }
+
+    #[test]
+    fn stat_override_tests() -> Result<(), DexError> {
+        let dex = Dex::default();
+
+        let punching_bag = dex.pokemon("flareon")?;
+
+        // Body Press uses the user's defence and defence boosts
+        let corviknight = dex.pokemon("corviknight")?;
+        dex.calc().attacker(corviknight).defender(punching_bag).move_("bodypress")?.calc(false).assert([48, 49, 49, 50, 50, 51, 51, 52, 53, 53, 54, 54, 55, 55, 56, 57]);
+        dex.calc().attacker(corviknight.boost(Stat::Attack, 2)).defender(punching_bag).move_("bodypress")?.calc(false).assert([48, 49, 49, 50, 50, 51, 51, 52, 53, 53, 54, 54, 55, 55, 56, 57]);
+        dex.calc().attacker(corviknight.boost(Stat::Defence, 2)).defender(punching_bag).move_("bodypress")?.calc(false).assert([95, 96, 97, 98, 99, 100, 101, 103, 104, 105, 106, 107, 108, 109, 110, 112]);
+
+        // Foul Play uses the target's attack and attack boosts
+        dex.calc().attacker(corviknight).defender(punching_bag).move_("foulplay")?.calc(false).assert([68, 68, 69, 70, 71, 72, 72, 73, 74, 75, 76, 76, 77, 78, 79, 80]);
+        dex.calc().attacker(corviknight.boost(Stat::Attack, -2)).defender(punching_bag.boost(Stat::Attack, 2)).move_("foulplay")?.calc(false).assert([134, 135, 137, 139, 140, 142, 143, 145, 146, 148, 150, 151, 153, 154, 156, 158]);
+
+        // Psystrike targets defence, which Wonder Room swaps with special defence
+        let mewtwo = dex.pokemon("mewtwo")?;
+        dex.calc().attacker(mewtwo).defender(punching_bag).move_("psystrike")?.calc(false).assert([123, 124, 126, 127, 129, 130, 132, 133, 135, 136, 138, 139, 141, 142, 144, 145]);
+        let wonder_room = Field::new().pseudo_weather(PseudoWeather::WonderRoom);
+        dex.calc().attacker(mewtwo).defender(punching_bag).move_("psystrike")?.field(wonder_room).calc(false).assert([76, 76, 78, 78, 79, 81, 81, 82, 82, 84, 85, 85, 87, 87, 88, 90]);
+
+        // Photon Geyser is physical when attack is higher
+        let necrozma = dex.pokemon("necrozmaduskmane")?;
+        dex.calc().attacker(necrozma).defender(punching_bag).move_("photongeyser")?.calc(false).assert([126, 127, 129, 130, 132, 133, 135, 136, 138, 139, 141, 142, 144, 145, 147, 148]);
+
+        // Psyshock and Secret Sword are special but target defence
+        for move_ in ["psyshock", "secretsword"] {
+            let calc = dex.calc().attacker(mewtwo).move_(move_)?;
+            let damage = calc.defender(punching_bag).calc(false).damage_range();
+            calc.defender(punching_bag.boost(Stat::SpecialDefence, 2)).calc(false).assert(damage.0);
+            assert!(calc.defender(punching_bag.boost(Stat::Defence, 2)).calc(false).damage_range().max() < damage.min());
+        }
+
+        // Shell Side Arm is physical when it would deal more damage, so lowering the other stat changes nothing
+        let slowbro = dex.pokemon("slowbrogalar")?;
+        let shell_side_arm = |attacker, defender| dex.calc().attacker(attacker).defender(defender).move_("shellsidearm").map(|calc| calc.calc(false).damage_range());
+        let blissey = dex.pokemon("blissey")?;
+        assert_eq!(shell_side_arm(slowbro.boost(Stat::SpecialAttack, -1), blissey)?, shell_side_arm(slowbro, blissey)?);
+        assert_ne!(shell_side_arm(slowbro.boost(Stat::Attack, -1), blissey)?, shell_side_arm(slowbro, blissey)?);
+        let cloyster = dex.pokemon("cloyster")?;
+        assert_eq!(shell_side_arm(slowbro.boost(Stat::Attack, -1), cloyster)?, shell_side_arm(slowbro, cloyster)?);
+        assert_ne!(shell_side_arm(slowbro.boost(Stat::SpecialAttack, -1), cloyster)?, shell_side_arm(slowbro, cloyster)?);
+
+        // Tera Blast is only physical, and only takes the tera type, when terastallized
+        let dragonite = dex.pokemon("dragonite")?;
+        let tera_blast = |attacker, defender| dex.calc().attacker(attacker).defender(defender).move_("terablast").map(|calc| calc.calc(false).damage_range());
+        assert_eq!(tera_blast(dragonite.boost(Stat::Attack, 2), punching_bag)?, tera_blast(dragonite, punching_bag)?);
+        let terastallized = dragonite.tera_type(Type::Flying);
+        assert!(tera_blast(terastallized.boost(Stat::Attack, 2), punching_bag)?.min() > tera_blast(terastallized, punching_bag)?.max());
+        let gengar = dex.pokemon("gengar")?;
+        assert_eq!(tera_blast(dragonite, gengar)?, [0; 16]);
+        assert!(tera_blast(terastallized, gengar)?.min() > 0);
+
         Ok(())
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn move_specific_tests() -> Result<(), DexError> {
+        let dex = Dex::default();
+
+        let punching_bag = dex.pokemon("flareon")?;
+
+        // Weather Ball
+        let castform = dex.pokemon("castform")?;
+        dex.calc().attacker(castform).defender(punching_bag).move_("weatherball")?.calc(false).assert([21, 21, 21, 21, 22, 22, 22, 22, 22, 22, 24, 24, 24, 24, 24, 25]);
+        dex.calc().attacker(castform).defender(punching_bag).move_("weatherball")?.field(Field::new().weather(Weather::RainDance)).calc(false).assert([54, 54, 54, 56, 56, 56, 58, 58, 58, 60, 60, 60, 62, 62, 62, 64]);
+
+        // Judgment
+        let arceus = dex.pokemon("arceus")?;
+        dex.calc().attacker(arceus.item(dex.item("splashplate")?)).defender(punching_bag).move_("judgment")?.calc(false).assert([82, 84, 84, 86, 86, 88, 88, 90, 90, 92, 92, 94, 94, 96, 96, 98]);
+
+        // Fling
+        dex.calc().attacker(arceus).defender(punching_bag).move_("fling")?.calc(false).assert([0; 16]);
+        dex.calc().attacker(arceus.item(dex.item("ironball")?)).defender(punching_bag).move_("fling")?.calc(false).assert([86, 87, 88, 89, 90, 91, 92, 93, 94, 95, 96, 97, 98, 99, 100, 102]);
+
+        // Terrain Pulse
+        dex.calc().attacker(arceus).defender(punching_bag).move_("terrainpulse")?.field(Field::new().terrain(Terrain::Psychic)).calc(false).assert([41, 42, 42, 43, 43, 44, 44, 45, 45, 46, 46, 47, 47, 48, 48, 49]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn retaliation_tests() -> Result<(), DexError> {
+        let dex = Dex::default();
+
+        let snorlax = dex.pokemon("snorlax")?;
+        let machamp = dex.pokemon("machamp")?;
+        let hit = dex.calc().attacker(machamp).defender(snorlax).move_("knockoff")?.calc(false);
+        let damage = hit.damage_range();
+        assert_eq!(hit.retaliation("counter")?, damage.0.map(|damage| damage * 2));
+        assert_eq!(hit.retaliation("metalburst")?, damage.0.map(|damage| damage * 3 / 2));
+        assert_eq!(hit.retaliation("mirrorcoat")?, [0; 16]);
+
+        // Counter is Fighting type, so it can't hit Ghost types
+        let gengar = dex.pokemon("gengar")?;
+        let hit = dex.calc().attacker(gengar).defender(snorlax).move_("icepunch")?.calc(false);
+        assert_eq!(hit.retaliation("counter")?, [0; 16]);
+        assert_ne!(hit.retaliation("bide")?, [0; 16]);
+
+        // Only the last hit of Double Kick counts
+        let double_kick = dex.calc().attacker(machamp).defender(snorlax).move_("doublekick")?.calc(false);
+        let one_kick = double_kick.damage_range().0.map(|damage| damage / 2);
+        assert_eq!(double_kick.retaliation("counter")?, one_kick.map(|damage| damage * 2));
+        assert_eq!(double_kick.retaliation("metalburst")?, one_kick.map(|damage| damage * 3 / 2));
+
+        // A KO leaves nothing to retaliate with
+        let hit = dex.calc().attacker(machamp.boost(Stat::Attack, 6)).defender(snorlax).move_("closecombat")?.calc(false);
+        assert_eq!(hit.retaliation("counter")?, [0; 16]);
+        // ...including when the defender was already damaged
+        let hit = dex.calc().attacker(machamp).defender(snorlax.current_hp(1)).move_("knockoff")?.calc(false);
+        assert_eq!(hit.retaliation("counter")?, [0; 16]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn battle_context_tests() -> Result<(), DexError> {
+        let dex = Dex::default();
+
+        let punching_bag = dex.pokemon("flareon")?;
+        let leftovers = punching_bag.item(dex.item("leftovers")?);
+
+        // Knock Off is stronger against a held item, unless it has already been removed
+        let weavile = dex.pokemon("weavile")?;
+        let knock_off = dex.calc().attacker(weavile).move_("knockoff")?;
+        let boosted = knock_off.defender(leftovers).calc(false).damage_range();
+        assert!(boosted.min() > knock_off.defender(punching_bag).calc(false).damage_range().max());
+        knock_off.defender(leftovers).context(BattleContext::new().defender_has_item(false)).calc(false).assert(knock_off.defender(punching_bag).calc(false).damage_range().0);
+        // ...but not against an item its holder can't lose
+        let zacian = dex.pokemon("zaciancrowned")?;
+        let unboosted = knock_off.defender(zacian).calc(false).damage_range();
+        knock_off.defender(zacian.item(dex.item("rustedsword")?)).calc(false).assert(unboosted.0);
+        assert!(knock_off.defender(zacian.item(dex.item("leftovers")?)).calc(false).damage_range().min() > unboosted.max());
+        knock_off.defender(zacian.item(dex.item("rustedsword")?)).context(BattleContext::new().defender_has_item(true)).calc(false).assert(unboosted.0);
+
+        // Revenge doubles in power after being hit
+        let machamp = dex.pokemon("machamp")?;
+        let revenge = dex.calc().attacker(machamp).defender(punching_bag).move_("revenge")?;
+        let hit = BattleContext::new().attacker_was_hit(true);
+        revenge.context(hit).calc(false).assert([127, 129, 130, 132, 133, 135, 136, 138, 139, 141, 142, 144, 145, 147, 148, 151]);
+
+        // Analytic
+        let magnezone = dex.pokemon("magnezone")?.ability(Ability::Analytic);
+        let thunderbolt = dex.calc().attacker(magnezone).defender(punching_bag).move_("thunderbolt")?;
+        assert!(thunderbolt.context(BattleContext::new().attacker_moves_last(true)).calc(false).damage_range().min() > thunderbolt.calc(false).damage_range().max());
+
+        // Fury Cutter caps at 160 power
+        let scizor = dex.pokemon("scizor")?;
+        let fury_cutter = dex.calc().attacker(scizor).defender(punching_bag).move_("furycutter")?;
+        let capped = fury_cutter.context(BattleContext::new().consecutive_uses(2)).calc(false).damage_range();
+        fury_cutter.context(BattleContext::new().consecutive_uses(10)).calc(false).assert(capped.0);
+        // Gen 4's Fury Cutter starts at 10 power, so it takes four uses to reach the cap
+        let gen_4 = Dex::generation_dex(Generation::Four);
+        let fury_cutter = gen_4.calc().attacker(gen_4.pokemon("scizor")?).defender(gen_4.pokemon("flareon")?).move_("furycutter")?;
+        let capped = fury_cutter.context(BattleContext::new().consecutive_uses(4)).calc(false).damage_range();
+        assert!(fury_cutter.context(BattleContext::new().consecutive_uses(3)).calc(false).damage_range().max() < capped.min());
+        fury_cutter.context(BattleContext::new().consecutive_uses(10)).calc(false).assert(capped.0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn target_state_tests() -> Result<(), DexError> {
+        let dex = Dex::default();
+
+        let punching_bag = dex.pokemon("flareon")?;
+        let garchomp = dex.pokemon("garchomp")?;
+        let earthquake = dex.calc().attacker(garchomp).move_("earthquake")?;
+        let normal = earthquake.defender(punching_bag).calc(false).damage_range();
+        // Doubling happens before some rounding, so it can be a few points off exactly double
+        let doubled = |outcome: CalcOutcome| outcome.damage_range().0.iter().zip(normal.0).all(|(doubled, damage)| doubled.abs_diff(damage * 2) <= 4);
+
+        // Earthquake hits Dig for double damage, but nothing hits Phantom Force
+        assert!(doubled(earthquake.defender(punching_bag.volatile(VolatileStatus::Dig)).calc(false)));
+        let vanished = earthquake.defender(punching_bag.volatile(VolatileStatus::PhantomForce)).calc(false);
+        assert_eq!(vanished.cannot_hit(), Some(CannotHit(VolatileStatus::PhantomForce)));
+        vanished.assert([0; 16]);
+        let flying = dex.calc().attacker(garchomp).defender(punching_bag.volatile(VolatileStatus::Fly)).move_("dragonclaw")?.calc(false);
+        assert_eq!(flying.cannot_hit(), Some(CannotHit(VolatileStatus::Fly)));
+
+        // Glaive Rush doubles damage taken
+        assert!(doubled(earthquake.defender(punching_bag.volatile(VolatileStatus::GlaiveRush)).calc(false)));
+
+        // Dynamax doubles HP and blocks weight based moves
+        let dynamaxed = punching_bag.volatile(VolatileStatus::Dynamax);
+        assert_eq!(dynamaxed.max_hp(), punching_bag.stat(Stat::HP) * 2);
+        let low_kick = dex.calc().attacker(garchomp).defender(dynamaxed).move_("lowkick")?.calc(false);
+        assert_eq!(low_kick.cannot_hit(), Some(CannotHit(VolatileStatus::Dynamax)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn crit_tests() -> Result<(), DexError> {
+        let dex = Dex::default();
+
+        let punching_bag = dex.pokemon("flareon")?;
+        let garchomp = dex.pokemon("garchomp")?;
+        let crit_chance = |attacker, move_: &str, defender| -> Result<f32, DexError> {
+            Ok(dex.calc().attacker(attacker).defender(defender).move_(move_)?.calc(false).crit_chance())
+        };
+        assert_eq!(crit_chance(garchomp, "earthquake", punching_bag)?, 1.0 / 24.0);
+        assert_eq!(crit_chance(garchomp, "stoneedge", punching_bag)?, 1.0 / 8.0);
+        let focused = garchomp.volatile(VolatileStatus::FocusEnergy).item(dex.item("scopelens")?);
+        assert_eq!(crit_chance(focused, "stoneedge", punching_bag)?, 1.0);
+        assert_eq!(crit_chance(focused, "stoneedge", dex.pokemon("cloyster")?.ability(Ability::ShellArmor))?, 0.0);
+
+        // Gen 1 crits depend on speed
+        let gen_one = Dex::generation_dex(Generation::One);
+        let tauros = gen_one.pokemon("tauros")?;
+        let bodyslam = gen_one.calc().attacker(tauros).defender(tauros).move_("bodyslam")?.calc(false);
+        assert_eq!(bodyslam.crit_chance(), 55.0 / 256.0);
+        // and double the level instead of the damage, ignoring stat stages
+        assert!(bodyslam.crit_damage_range().max() < 2 * bodyslam.damage_range().max());
+        let boosted = gen_one.calc().attacker(tauros.boost(Stat::Attack, 2)).defender(tauros.boost(Stat::Defence, -2)).move_("bodyslam")?.calc(false);
+        assert_eq!(boosted.crit_damage_range(), bodyslam.crit_damage_range());
+
+        // A roll that only knocks out on a crit
+        let calc = dex.calc().attacker(garchomp).defender(punching_bag).move_("earthquake")?.calc(false);
+        let hp = calc.damage_range().max() as u16 + 1;
+        assert_eq!(calc.ko_chance(hp), 1.0 / 24.0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn random_power_tests() -> Result<(), DexError> {
+        let dex = Dex::default();
+
+        let punching_bag = dex.pokemon("flareon")?;
+        let garchomp = dex.pokemon("garchomp")?;
+
+        // Magnitude 7 is the most likely, and only Magnitude 10 knocks out
+        let magnitude = dex.calc().attacker(garchomp).defender(punching_bag).move_("magnitude")?.calc(false);
+        assert_eq!(magnitude.power_outcomes().len(), 7);
+        let earthquake = dex.calc().attacker(garchomp).defender(punching_bag).move_("earthquake")?.calc(false);
+        assert!(magnitude.damage_range().max() < earthquake.damage_range().min());
+        let magnitude_10 = magnitude.power_outcomes()[6];
+        let hp = magnitude_10.damage.min() as u16;
+        assert!((magnitude.ko_chance(hp) - 0.05).abs() < 0.01);
+
+        // Present heals 20% of the time
+        let present = dex.calc().attacker(garchomp).defender(punching_bag).move_("present")?.calc(false);
+        let heal = present.power_outcomes().iter().find(|outcome| outcome.heal > 0).expect("Present to heal");
+        assert_eq!((heal.chance, heal.heal), (0.2, punching_bag.stat(Stat::HP) / 4));
+
+        // Beat Up hits once per party member
+        let weavile = dex.pokemon("weavile")?;
+        let alone = dex.calc().attacker(weavile).defender(punching_bag).move_("beatup")?;
+        let party = alone.context(BattleContext::new().party_base_attack([Some(120), Some(120), None, None, None, None])).calc(false);
+        assert_eq!(party.damage_range(), alone.calc(false).damage_range() + alone.calc(false).damage_range());
+
+        // Spit Up fails without Stockpile
+        let swalot = dex.pokemon("swalot")?;
+        dex.calc().attacker(swalot).defender(punching_bag).move_("spitup")?.calc(false).assert([0; 16]);
+        let spit_up = dex.calc().attacker(swalot.stockpile(3)).defender(punching_bag).move_("spitup")?.calc(false);
+        assert!(spit_up.damage_range().min() > 0);
+
+        Ok(())
+    }
+}
@@ -1,6 +1,6 @@
 use std::{collections::HashMap, fmt::Display};
 
-use crate::{items::ItemData, learnsets::Learnset, moves::MoveData, natures::NatureData, pokemon::Pokemon, species::Species, types::TypeData};
+use crate::{generation::Generation, items::ItemData, learnsets::Learnset, moves::{MoveData, NonStandardReason}, natures::NatureData, pokemon::Pokemon, species::Species, types::TypeData};
 
 pub trait Identifier {
     fn as_identifier(&self) -> String;
@@ -12,6 +12,8 @@ impl<T: AsRef<str>> Identifier for T {
 }
 
 pub struct Dex {
+    gen: Generation,
+    lets_go: bool,
     moves: HashMap<String, MoveData>,
     species: HashMap<String, Species>,
     types: HashMap<String, TypeData>,
@@ -20,7 +22,7 @@ pub struct Dex {
     items: HashMap<String, ItemData>
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum DexError {
     NotFound(String)
 }
@@ -32,8 +34,29 @@ impl Display for DexError {
     }
 }
 impl Dex {
-    pub fn new(moves: HashMap<String, MoveData>, species: HashMap<String, Species>, types: HashMap<String, TypeData>, learnsets: HashMap<String, Learnset>, natures: HashMap<String, NatureData>, items: HashMap<String, ItemData>) -> Self {
-        Self { moves, species, types, learnsets, natures, items }
+    pub fn new(gen: Generation, moves: HashMap<String, MoveData>, species: HashMap<String, Species>, types: HashMap<String, TypeData>, learnsets: HashMap<String, Learnset>, natures: HashMap<String, NatureData>, items: HashMap<String, ItemData>) -> Self {
+        Self { gen, lets_go: false, moves, species, types, learnsets, natures, items }
+    }
+    pub fn generation(&self) -> Generation {
+        self.gen
+    }
+    /// Whether this dex only has Let's Go Pikachu/Eevee species and moves. See [Dex::into_lets_go].
+    pub fn is_lets_go(&self) -> bool {
+        self.lets_go
+    }
+    /// Restricts a Gen 7 dex to Let's Go Pikachu/Eevee: the Kanto pokemon, their Alolan and Mega formes, Meltan, Melmetal and the partner pokemon,
+    /// and the Gen 1 moves, the Let's Go exclusive moves and [LETS_GO_LATER_MOVES].
+    pub fn into_lets_go(mut self) -> Self {
+        self.lets_go = true;
+        self.species.retain(|_, species| is_lets_go_species(species));
+        self.moves.retain(|id, move_| {
+            matches!(move_.num, 1..=165) || move_.is_nonstandard == Some(NonStandardReason::LGPE) || LETS_GO_LATER_MOVES.contains(&id.as_str())
+        });
+        self
+    }
+    /// Why something isn't available in this dex, if it isn't. Let's Go exclusives are only available in a Let's Go dex.
+    pub fn nonstandard_reason(&self, is_nonstandard: Option<NonStandardReason>) -> Option<NonStandardReason> {
+        is_nonstandard.filter(|reason| !(self.lets_go && *reason == NonStandardReason::LGPE))
     }
     
     pub fn move_<Id: Identifier>(&self, identifier:Id) -> Result<&MoveData, DexError> {
@@ -54,7 +77,27 @@ impl Dex {
     pub fn item<Id: Identifier>(&self, identifier:Id) -> Result<&ItemData, DexError> {
         self.items.get(&identifier.as_identifier()).ok_or(DexError::NotFound(identifier.as_identifier()))
     }
-    pub fn pokemon<Id: Identifier>(&self, identifier:Id) -> Result<Pokemon, DexError> {
-        Ok(Pokemon::new(self.species(identifier)?))
+    pub fn pokemon<Id: Identifier>(&self, identifier:Id) -> Result<Pokemon<'_>, DexError> {
+        Ok(Pokemon::new(self.species(identifier)?, self.gen).lets_go(self.lets_go))
     }
 }
+
+/// The moves from after Gen 1 that Let's Go Pikachu/Eevee has, from its TMs and level up learnsets.
+pub const LETS_GO_LATER_MOVES: [&str; 27] = [
+    "airslash", "brickbreak", "bulkup", "calmmind", "crunch", "dazzlinggleam", "dragondance", "dragonpulse", "drillrun",
+    "facade", "hurricane", "ironhead", "liquidation", "megahorn", "moonblast", "outrage", "playrough", "poisonjab",
+    "protect", "roost", "scald", "shadowball", "sludgebomb", "steelwing", "superpower", "willowisp", "xscissor",
+];
+
+fn is_lets_go_species(species: &Species) -> bool {
+    let kanto = matches!(species.num, 1..=151 | 808 | 809);
+    let forme = match species.forme.as_deref() {
+        None | Some("Starter") => true,
+        // Pikachu's Alola forme is a cap, not an Alolan forme
+        Some(_) if species.base_species.as_deref() == Some("Pikachu") => false,
+        Some(forme) => forme == "Alola" || forme.starts_with("Mega"),
+    };
+    // Meltan, Melmetal and the partner pokemon are from the future in Gen 7
+    let obtainable = matches!(species.is_nonstandard, None | Some(NonStandardReason::Future | NonStandardReason::LGPE));
+    kanto && forme && obtainable
+}
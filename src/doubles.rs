@@ -0,0 +1,170 @@
+use crate::{field::Field, moves::{MoveData, Target, VolatileStatus}, names::abilities::Ability, pokemon::Pokemon, types::Type};
+
+/// Which side of a double battle a pokemon is on, from the attacker's point of view.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Team {
+    Own,
+    Opposing,
+}
+
+/// Which of the two slots on a side of a double battle a pokemon is in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Position {
+    Left,
+    Right,
+}
+impl Position {
+    pub fn other(self) -> Self {
+        match self {
+            Position::Left => Position::Right,
+            Position::Right => Position::Left,
+        }
+    }
+    fn index(self) -> usize {
+        match self {
+            Position::Left => 0,
+            Position::Right => 1,
+        }
+    }
+}
+
+/// A position in a double battle.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Slot(pub Team, pub Position);
+impl Slot {
+    /// The other slot on the same side.
+    pub fn partner(self) -> Self {
+        Self(self.0, self.1.other())
+    }
+}
+
+/// The pokemon in each slot of a double battle. Empty slots have fainted or were never filled.
+#[derive(Clone, Copy, Default)]
+pub struct Doubles<'a> {
+    own: [Option<Pokemon<'a>>; 2],
+    opposing: [Option<Pokemon<'a>>; 2],
+}
+impl<'a> Doubles<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn get(&self, slot: Slot) -> Option<&Pokemon<'a>> {
+        match slot.0 {
+            Team::Own => self.own[slot.1.index()].as_ref(),
+            Team::Opposing => self.opposing[slot.1.index()].as_ref(),
+        }
+    }
+    fn occupied(&self, slot: Slot) -> Option<Slot> {
+        self.get(slot).map(|_| slot)
+    }
+
+    /// The pokemon a move actually hits, after redirection. `target` is only used for single target moves.
+    pub fn targets(&self, user: Slot, target: Slot, move_: &MoveData, field: &Field) -> Vec<Slot> {
+        let opponents = |slot: Slot| match slot.0 {
+            Team::Own => [Slot(Team::Opposing, Position::Left), Slot(Team::Opposing, Position::Right)],
+            Team::Opposing => [Slot(Team::Own, Position::Left), Slot(Team::Own, Position::Right)],
+        };
+        match move_.target {
+            Target::AllAdjacentFoes => opponents(user).into_iter().filter_map(|slot| self.occupied(slot)).collect(),
+            Target::AllAdjacent => opponents(user).into_iter().chain([user.partner()]).filter_map(|slot| self.occupied(slot)).collect(),
+            Target::AdjacentAlly => self.occupied(user.partner()).into_iter().collect(),
+            Target::Normal | Target::Any | Target::AdjacentFoe | Target::RandomNormal | Target::AdjacentAllyOrSelf => {
+                let target = self.redirect(user, target, move_, field).or_else(|| self.occupied(target))
+                    // Moves aimed at a fainted foe go to the other foe
+                    .or_else(|| (target.0 != user.0).then(|| self.occupied(target.partner())).flatten());
+                target.into_iter().collect()
+            },
+            _ => vec![]
+        }
+    }
+
+    /// The slot that draws in a single target move instead of its target. See Follow Me and Lightning Rod.
+    fn redirect(&self, user: Slot, target: Slot, move_: &MoveData, field: &Field) -> Option<Slot> {
+        let attacker = self.get(user)?;
+        if move_.tracks_target || [Ability::Stalwart, Ability::PropellerTail].contains(&attacker.ability) {
+            return None
+        }
+        let candidates = [Slot(Team::Own, Position::Left), Slot(Team::Own, Position::Right), Slot(Team::Opposing, Position::Left), Slot(Team::Opposing, Position::Right)]
+            .into_iter()
+            .filter(|slot| *slot != user && *slot != target)
+            .filter_map(|slot| self.get(slot).map(|pokemon| (slot, pokemon)));
+
+        let powder_immune = attacker.species.types.contains(&Type::Grass) || attacker.ability == Ability::Overcoat
+            || attacker.active_item(field).is_some_and(|item| item.id() == "safetygoggles");
+        // Follow Me and Rage Powder only draw in moves from the other side
+        let center_of_attention = candidates.clone()
+            .filter(|(slot, _)| slot.0 != user.0 && target.0 != user.0)
+            .find(|(_, pokemon)| {
+                pokemon.volatiles.contains(VolatileStatus::FollowMe) || (pokemon.volatiles.contains(VolatileStatus::RagePowder) && !powder_immune)
+            });
+        if let Some((slot, _)) = center_of_attention {
+            return Some(slot)
+        }
+        let lightning_rod = match move_.type_ {
+            Type::Electric => Ability::LightningRod,
+            Type::Water => Ability::StormDrain,
+            _ => return None
+        };
+        candidates.filter(|(_, pokemon)| pokemon.ability == lightning_rod).map(|(slot, _)| slot).next()
+    }
+}
+
+impl<'a> Doubles<'a> {
+    pub fn pokemon(mut self, slot: Slot, pokemon: Pokemon<'a>) -> Self {
+        match slot.0 {
+            Team::Own => self.own[slot.1.index()] = Some(pokemon),
+            Team::Opposing => self.opposing[slot.1.index()] = Some(pokemon),
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "real_data")]
+mod tests {
+    use crate::{dex::{Dex, DexError}, moves::VolatileStatus, names::abilities::Ability};
+
+    use super::{Doubles, Position, Slot, Team};
+
+    #[test]
+    fn doubles_tests() -> Result<(), DexError> {
+        let dex = Dex::default();
+        let user = Slot(Team::Own, Position::Left);
+        let left_foe = Slot(Team::Opposing, Position::Left);
+        let right_foe = Slot(Team::Opposing, Position::Right);
+
+        let garchomp = dex.pokemon("garchomp")?;
+        let incineroar = dex.pokemon("incineroar")?;
+        let field = Doubles::new()
+            .pokemon(user, garchomp)
+            .pokemon(user.partner(), dex.pokemon("pelipper")?)
+            .pokemon(left_foe, incineroar)
+            .pokemon(right_foe, dex.pokemon("amoonguss")?);
+
+        // Earthquake hits everyone else, including the ally
+        let earthquake = dex.calc().attacker(garchomp).move_("earthquake")?;
+        let hits = earthquake.doubles(&field, user, left_foe);
+        assert_eq!(hits.iter().map(|(slot, _)| *slot).collect::<Vec<_>>(), [left_foe, right_foe, user.partner()]);
+
+        // No spread reduction with only one target
+        let alone = Doubles::new().pokemon(user, garchomp).pokemon(left_foe, incineroar);
+        let single = earthquake.doubles(&alone, user, left_foe);
+        assert_eq!(single[0].1.damage_range(), earthquake.defender(incineroar).calc(false).damage_range());
+        assert!(hits[0].1.damage_range().max() < single[0].1.damage_range().min());
+
+        // Follow Me draws in single target moves, except Snipe Shot
+        let follow_me = field.pokemon(right_foe, dex.pokemon("clefairy")?.volatile(VolatileStatus::FollowMe));
+        let hits = dex.calc().attacker(garchomp).move_("dragonclaw")?.doubles(&follow_me, user, left_foe);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].0, right_foe);
+        let hits = dex.calc().attacker(garchomp).move_("snipeshot")?.doubles(&follow_me, user, left_foe);
+        assert_eq!(hits[0].0, left_foe);
+
+        // Storm Drain draws in Water moves
+        let storm_drain = field.pokemon(right_foe, dex.pokemon("gastrodon")?.ability(Ability::StormDrain));
+        let hits = dex.calc().attacker(garchomp).move_("liquidation")?.doubles(&storm_drain, user, left_foe);
+        assert_eq!(hits[0].0, right_foe);
+        assert_eq!(hits[0].1.damage_range(), [0; 16]);
+        Ok(())
+    }
+}
@@ -0,0 +1,190 @@
+use std::fmt::Display;
+
+use crate::{dex::Dex, generation::Generation, learnsets::{EncounterData, EventShiny, Gender, LearnMethod, ResolvedLearnset}, names::moves::Move, natures::Nature, paste::{to_id, TeamMember}, species::Stat};
+
+const STATS: [Stat; 6] = [Stat::HP, Stat::Attack, Stat::Defence, Stat::SpecialAttack, Stat::SpecialDefence, Stat::Speed];
+
+/// Where a pokemon came from: an event or a wild encounter in the learnset of a species in its evolution line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EncounterSource<'a> {
+    /// The index in the species' [Learnset::event_data](crate::learnsets::Learnset::event_data).
+    Event { species: &'a str, index: usize },
+    /// The index in the species' [Learnset::encounters](crate::learnsets::Learnset::encounters).
+    Encounter { species: &'a str, index: usize },
+}
+
+/// Why a pokemon can't have come from an event or encounter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EncounterMismatch {
+    /// The event is from a later generation than the Dex.
+    Generation(Generation),
+    /// The pokemon is below the event's level.
+    Level(u8),
+    Iv { stat: Stat, required: u8 },
+    /// Fewer than this many IVs are 31.
+    PerfectIvs(u8),
+    Shiny(EventShiny),
+    Nature(Nature),
+    Pokeball(String),
+    Gender(Gender),
+    /// The event gives the hidden ability, and the pokemon doesn't have it, or the other way round.
+    HiddenAbility(bool),
+    /// An ability the event doesn't give.
+    Ability,
+    /// A move the event doesn't have, that can't be learnt another way.
+    Move(String),
+}
+impl Display for EncounterMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Generation(gen) => write!(f, "The event is from Gen {}", *gen as u8 + 1),
+            Self::Level(level) => write!(f, "Must be at least level {level}"),
+            Self::Iv { stat, required } => write!(f, "{stat:?} IV must be {required}"),
+            Self::PerfectIvs(count) => write!(f, "At least {count} IVs must be 31"),
+            Self::Shiny(shiny) => write!(f, "Shininess must be {shiny:?}"),
+            Self::Nature(nature) => write!(f, "Nature must be {nature:?}"),
+            Self::Pokeball(pokeball) => write!(f, "Must be in {pokeball}"),
+            Self::Gender(gender) => write!(f, "Gender must be {gender:?}"),
+            Self::HiddenAbility(hidden) => write!(f, "Must {}have the hidden ability", if *hidden {""} else {"not "}),
+            Self::Ability => write!(f, "The event doesn't give this ability"),
+            Self::Move(move_) => write!(f, "The event doesn't have {move_}, and it can't be learnt another way"),
+        }
+    }
+}
+
+impl Dex {
+    /// Every way a pokemon doesn't match an event or encounter.
+    /// Moves not from the event are allowed if they can be learnt some way other than an event, in this generation or before.
+    pub fn check_encounter(&self, member: &TeamMember, encounter: &EncounterData) -> Vec<EncounterMismatch> {
+        let pokemon = &member.pokemon;
+        let mut mismatches = vec![];
+        if encounter.generation > self.generation() {
+            mismatches.push(EncounterMismatch::Generation(encounter.generation));
+        }
+        if let Some(level) = encounter.level.filter(|level| pokemon.level < *level) {
+            mismatches.push(EncounterMismatch::Level(level));
+        }
+
+        for stat in STATS {
+            let required = encounter.ivs.and_then(|ivs| ivs.get(stat));
+            if let Some(required) = required.filter(|required| pokemon.ivs.get(stat) != *required) {
+                mismatches.push(EncounterMismatch::Iv { stat, required });
+            }
+        }
+        let perfect_ivs = STATS.iter().filter(|stat| pokemon.ivs.get(**stat) == 31).count() as u8;
+        if let Some(required) = encounter.perfect_ivs.filter(|required| perfect_ivs < *required) {
+            mismatches.push(EncounterMismatch::PerfectIvs(required));
+        }
+
+        let shiny_matches = match encounter.shiny {
+            EventShiny::Shiny => pokemon.shiny,
+            EventShiny::NotShiny => !pokemon.shiny,
+            EventShiny::CanBeEither => true,
+        };
+        if !shiny_matches {
+            mismatches.push(EncounterMismatch::Shiny(encounter.shiny));
+        }
+        if let Some(nature) = encounter.nature.filter(|nature| pokemon.nature.is_none_or(|actual| to_id(&actual.name) != nature.to_string())) {
+            mismatches.push(EncounterMismatch::Nature(nature));
+        }
+        if let Some(pokeball) = encounter.pokeball.as_ref().filter(|pokeball| member.pokeball.as_ref().is_some_and(|actual| to_id(actual) != to_id(pokeball))) {
+            mismatches.push(EncounterMismatch::Pokeball(pokeball.clone()));
+        }
+        if let Some(gender) = encounter.gender.filter(|gender| pokemon.gender.is_some_and(|actual| actual != *gender)) {
+            mismatches.push(EncounterMismatch::Gender(gender));
+        }
+
+        let ability = to_id(&pokemon.ability.name());
+        let hidden = pokemon.species.abilities.hidden.is_some_and(|hidden| hidden == pokemon.ability)
+            && !pokemon.species.abilities.normal.contains(&pokemon.ability);
+        match &encounter.abilities {
+            Some(abilities) if !abilities.iter().any(|allowed| to_id(allowed) == ability) => mismatches.push(EncounterMismatch::Ability),
+            Some(_) => {},
+            None if hidden != encounter.is_hidden => mismatches.push(EncounterMismatch::HiddenAbility(encounter.is_hidden)),
+            None => {},
+        }
+
+        let learnset = self.resolved_learnset(pokemon.species);
+        let event_moves = encounter.moves.iter().flatten().map(|move_| to_id(move_)).collect::<Vec<_>>();
+        for slot in pokemon.moves() {
+            if !event_moves.contains(&to_id(&slot.move_.name)) && !self.learnable_outside_events(&learnset, slot.move_.id()) {
+                mismatches.push(EncounterMismatch::Move(slot.move_.name.clone()));
+            }
+        }
+        mismatches
+    }
+
+    fn learnable_outside_events(&self, learnset: &ResolvedLearnset, move_: Option<Move>) -> bool {
+        // Without learnset data, moves can't be ruled out
+        if learnset.is_empty() {
+            return true
+        }
+        move_.is_some_and(|move_| learnset.sources(move_).iter()
+            .any(|source| source.source.gen <= self.generation() && !matches!(source.source.method, LearnMethod::Event(_))))
+    }
+
+    /// Every event and encounter a pokemon could have come from, from it or any of its prevolutions.
+    pub fn legal_encounters<'a>(&'a self, member: &TeamMember<'a>) -> Vec<EncounterSource<'a>> {
+        let mut sources = vec![];
+        for species in self.evolution_line(member.pokemon.species) {
+            let Some((name, learnset)) = self.own_learnset(species) else {
+                continue
+            };
+            let matches = |encounter: &EncounterData| self.check_encounter(member, encounter).is_empty();
+            sources.extend(learnset.event_data.iter().flatten().enumerate()
+                .filter(|(_, event)| matches(event))
+                .map(|(index, _)| EncounterSource::Event { species: name, index }));
+            sources.extend(learnset.encounters.iter().enumerate()
+                .filter(|(_, encounter)| matches(encounter))
+                .map(|(index, _)| EncounterSource::Encounter { species: name, index }));
+        }
+        sources
+    }
+
+    /// Whether a pokemon's species can only be obtained from events. See Mew.
+    pub fn is_event_only(&self, member: &TeamMember) -> bool {
+        self.own_learnset(member.pokemon.species).is_some_and(|(_, learnset)| learnset.event_only)
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "real_data")]
+mod tests {
+    use crate::{dex::Dex, generation::Generation, natures::Nature, paste::TeamMember, species::Stat, validator::Problem};
+
+    use super::{EncounterMismatch, EncounterSource};
+
+    #[test]
+    fn encounter_tests() {
+        let data = Dex::default();
+        let species = [("mew".to_string(), data.species("mew").unwrap().clone())].into();
+        let moves = ["pound", "psychic", "transform"].map(|id| (id.to_string(), data.move_(id).unwrap().clone())).into();
+        let learnsets = serde_json::from_str(r#"{"mew": {
+            "learnset": {"pound": ["9L1"], "psychic": ["9M"], "transform": ["9S0"]},
+            "eventData": [
+                {"generation": 9, "level": 5, "shiny": 1, "moves": ["pound", "transform"], "pokeball": "pokeball", "perfectIVs": 3},
+                {"generation": 8, "level": 70, "nature": "Timid", "ivs": {"hp": 31, "spe": 31}, "moves": ["psychic"]}
+            ],
+            "eventOnly": true
+        }}"#).unwrap();
+        let dex = Dex::new(Generation::Nine, moves, species, Default::default(), learnsets, Default::default(), Default::default());
+
+        let mew = dex.pokemon("mew").unwrap().level(50).move_(dex.move_("transform").unwrap()).move_(dex.move_("psychic").unwrap());
+        let member = TeamMember::new(mew);
+        assert_eq!(dex.legal_encounters(&member), [EncounterSource::Event { species: "Mew", index: 0 }]);
+        assert_eq!(dex.validate_pokemon(&member), []);
+
+        // Transform only comes from the first event
+        let mew = mew.level(100).ivs([0, 0, 0, 0, 0, 31]);
+        let member = TeamMember::new(mew);
+        let events = dex.learnset("mew").unwrap().event_data.as_ref().unwrap();
+        assert_eq!(dex.check_encounter(&member, &events[0]), [EncounterMismatch::PerfectIvs(3)]);
+        assert_eq!(dex.check_encounter(&member, &events[1]), [
+            EncounterMismatch::Iv { stat: Stat::HP, required: 31 },
+            EncounterMismatch::Nature(Nature::Timid),
+            EncounterMismatch::Move("Transform".to_string()),
+        ]);
+        assert_eq!(dex.legal_encounters(&member), []);
+        assert!(dex.validate_pokemon(&member).contains(&Problem::NoMatchingEvent));
+    }
+}
@@ -0,0 +1,59 @@
+use std::{fmt::Debug, marker::PhantomData};
+
+use crate::moves::{PseudoWeather, SideCondition, VolatileStatus};
+
+/// A fieldless enum with at most 128 variants, so that it can be stored in an [EnumSet].
+pub trait SetMember: Copy {
+    fn index(self) -> u8;
+}
+
+/// **impl_set_member!(T, ...)** where T is a fieldless enum with at most 128 variants.\
+/// Implements SetMember for T using the variant's discriminant.
+macro_rules! impl_set_member {
+    ($($t: ident),*) => {
+        $(impl SetMember for $t {
+            fn index(self) -> u8 {
+                self as u8
+            }
+        })*
+    };
+}
+impl_set_member!(PseudoWeather, SideCondition, VolatileStatus);
+
+/// A set of enum variants which, unlike a `Vec`, is `Copy`. Used for state that is attached to a pokemon or the field.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct EnumSet<T: SetMember>(u128, PhantomData<T>);
+impl<T: SetMember> EnumSet<T> {
+    pub const fn new() -> Self {
+        Self(0, PhantomData)
+    }
+    pub fn insert(&mut self, member: T) {
+        self.0 |= 1 << member.index();
+    }
+    pub fn remove(&mut self, member: T) {
+        self.0 &= !(1 << member.index());
+    }
+    pub fn contains(&self, member: T) -> bool {
+        self.0 & (1 << member.index()) != 0
+    }
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+}
+impl<T: SetMember> Default for EnumSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<T: SetMember> FromIterator<T> for EnumSet<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut set = Self::new();
+        iter.into_iter().for_each(|member| set.insert(member));
+        set
+    }
+}
+impl<T: SetMember> Debug for EnumSet<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "EnumSet({:#b})", self.0)
+    }
+}
@@ -0,0 +1,93 @@
+use crate::{enum_set::EnumSet, moves::{PseudoWeather, SideCondition, Terrain, Weather}};
+
+/// Battle state that affects every pokemon on the field.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Field {
+    pub weather: Option<Weather>,
+    pub terrain: Option<Terrain>,
+    pub pseudo_weather: EnumSet<PseudoWeather>,
+    /// Whether a pokemon with Air Lock or Cloud Nine is on the field, which suppresses the weather.
+    pub air_lock: bool,
+    pub attacker_side: Side,
+    pub defender_side: Side,
+}
+impl Field {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn has_pseudo_weather(&self, pseudo_weather: PseudoWeather) -> bool {
+        self.pseudo_weather.contains(pseudo_weather)
+    }
+    /// The weather, unless it is suppressed by Air Lock or Cloud Nine.
+    pub fn active_weather(&self) -> Option<Weather> {
+        self.weather.filter(|_| !self.air_lock)
+    }
+}
+
+impl Field {
+    pub fn weather(mut self, weather: Weather) -> Self {
+        self.weather = Some(weather);
+        self
+    }
+    pub fn terrain(mut self, terrain: Terrain) -> Self {
+        self.terrain = Some(terrain);
+        self
+    }
+    pub fn pseudo_weather(mut self, pseudo_weather: PseudoWeather) -> Self {
+        self.pseudo_weather.insert(pseudo_weather);
+        self
+    }
+    pub fn air_lock(mut self, air_lock: bool) -> Self {
+        self.air_lock = air_lock;
+        self
+    }
+    pub fn attacker_side(mut self, side: Side) -> Self {
+        self.attacker_side = side;
+        self
+    }
+    pub fn defender_side(mut self, side: Side) -> Self {
+        self.defender_side = side;
+        self
+    }
+}
+
+/// Battle state that affects one side of the field.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Side {
+    pub conditions: EnumSet<SideCondition>,
+    /// Layers of Spikes, from 0 to 3.
+    pub spikes: u8,
+    /// Layers of Toxic Spikes, from 0 to 2.
+    pub toxic_spikes: u8,
+}
+impl Side {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn has_condition(&self, condition: SideCondition) -> bool {
+        match condition {
+            SideCondition::Spikes => self.spikes > 0,
+            SideCondition::ToxicSpikes => self.toxic_spikes > 0,
+            _ => self.conditions.contains(condition)
+        }
+    }
+}
+
+impl Side {
+    pub fn condition(mut self, condition: SideCondition) -> Self {
+        match condition {
+            SideCondition::Spikes => self.spikes = (self.spikes + 1).min(3),
+            SideCondition::ToxicSpikes => self.toxic_spikes = (self.toxic_spikes + 1).min(2),
+            _ => self.conditions.insert(condition)
+        }
+        self
+    }
+    pub fn spikes(mut self, layers: u8) -> Self {
+        self.spikes = layers.min(3);
+        self
+    }
+    pub fn toxic_spikes(mut self, layers: u8) -> Self {
+        self.toxic_spikes = layers.min(2);
+        self
+    }
+}
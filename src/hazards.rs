@@ -0,0 +1,124 @@
+use crate::{dex::{Dex, DexError}, field::{Field, Side}, moves::{SideCondition, Status, Terrain}, names::abilities::Ability, pokemon::Pokemon, residual::fraction, types::Type};
+
+/// What happens to a pokemon when it switches in to a side with entry hazards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SwitchIn {
+    pub stealth_rock: u16,
+    pub spikes: u16,
+    /// The status inflicted by Toxic Spikes.
+    pub status: Option<Status>,
+    /// Whether this pokemon is a grounded Poison type that removes Toxic Spikes.
+    pub absorbs_toxic_spikes: bool,
+    /// The Speed stage change from Sticky Web.
+    pub speed_boost: i8,
+}
+impl SwitchIn {
+    pub fn damage(&self) -> u16 {
+        self.stealth_rock + self.spikes
+    }
+    /// The HP left after switching in at full HP.
+    pub fn hp_after(&self, max_hp: u16) -> u16 {
+        max_hp.saturating_sub(self.damage())
+    }
+}
+
+impl Pokemon<'_> {
+    /// Entry hazard damage, status and stat drops this pokemon takes switching in to the given side,
+    /// which is usually one of `field.attacker_side` and `field.defender_side`.
+    pub fn switch_in(&self, dex: &Dex, field: &Field, side: &Side) -> Result<SwitchIn, DexError> {
+        let mut switch_in = SwitchIn::default();
+        if self.active_item(field).is_some_and(|item| item.id() == "heavydutyboots") {
+            return Ok(switch_in)
+        }
+        let max_hp = self.max_hp();
+        let magic_guard = self.ability == Ability::MagicGuard;
+        let grounded = self.is_grounded(field);
+        let has_type = |type_: Type| self.species.types.contains(&type_);
+
+        if side.has_condition(SideCondition::StealthRock) && !magic_guard {
+            let mut effectiveness = 1.0;
+            for type_ in self.species.types.iter() {
+                effectiveness *= dex.type_(type_)?.damage_taken(Type::Rock).to_multiplier();
+            }
+            if effectiveness > 0.0 {
+                switch_in.stealth_rock = ((max_hp as f32 * effectiveness / 8.0) as u16).max(1);
+            }
+        }
+        if !grounded {
+            return Ok(switch_in)
+        }
+
+        if side.spikes > 0 && !magic_guard {
+            let numerator = match side.spikes {
+                1 => 3,
+                2 => 4,
+                _ => 6
+            };
+            switch_in.spikes = fraction(max_hp, numerator, 24) as u16;
+        }
+
+        if side.toxic_spikes > 0 {
+            if has_type(Type::Poison) {
+                switch_in.absorbs_toxic_spikes = true;
+            } else {
+                let immune = self.status.is_some() || has_type(Type::Steel) || self.ability == Ability::Immunity
+                    || side.has_condition(SideCondition::SafeGuard) || field.terrain == Some(Terrain::Misty);
+                if !immune {
+                    switch_in.status = Some(if side.toxic_spikes >= 2 {Status::Toxic} else {Status::Poison});
+                }
+            }
+        }
+
+        if side.has_condition(SideCondition::StickyWeb) {
+            let blocked = [Ability::ClearBody, Ability::WhiteSmoke, Ability::FullMetalBody].contains(&self.ability)
+                || self.active_item(field).is_some_and(|item| item.id() == "clearamulet");
+            switch_in.speed_boost = match self.ability {
+                _ if blocked => 0,
+                Ability::Contrary => 1,
+                Ability::Simple => -2,
+                _ => -1
+            };
+        }
+        Ok(switch_in)
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "real_data")]
+mod tests {
+    use crate::{dex::{Dex, DexError}, field::{Field, Side}, moves::{SideCondition, Status}};
+
+    #[test]
+    fn hazard_tests() -> Result<(), DexError> {
+        let dex = Dex::default();
+        let field = Field::new();
+        let side = Side::new().condition(SideCondition::StealthRock).spikes(3).toxic_spikes(2).condition(SideCondition::StickyWeb);
+
+        let volcarona = dex.pokemon("volcarona")?;
+        let switch_in = volcarona.switch_in(&dex, &field, &side)?;
+        assert_eq!(switch_in.stealth_rock, volcarona.max_hp() / 2);
+        assert_eq!(switch_in.spikes, volcarona.max_hp() / 4);
+        assert_eq!(switch_in.status, Some(Status::Toxic));
+        assert_eq!(switch_in.speed_boost, -1);
+
+        let booted = volcarona.item(dex.item("heavydutyboots")?);
+        assert_eq!(booted.switch_in(&dex, &field, &side)?.damage(), 0);
+
+        let toxapex = dex.pokemon("toxapex")?;
+        let switch_in = toxapex.switch_in(&dex, &field, &side)?;
+        assert!(switch_in.absorbs_toxic_spikes);
+        assert_eq!(switch_in.status, None);
+
+        let corviknight = dex.pokemon("corviknight")?;
+        let switch_in = corviknight.switch_in(&dex, &field, &side)?;
+        assert_eq!(switch_in.damage(), corviknight.max_hp() / 8);
+        assert_eq!(switch_in.speed_boost, 0);
+
+        // Stealth Rock turns a roll into a guaranteed KO
+        let rocks = Field::new().defender_side(Side::new().condition(SideCondition::StealthRock));
+        let calc = dex.calc().attacker(dex.pokemon("garchomp")?).defender(volcarona).move_("smackdown")?.field(rocks).calc(false);
+        assert!(calc.ko_chance(volcarona.max_hp()) < 1.0);
+        assert_eq!(calc.ko_chance_after_hazards()?, 1.0);
+        Ok(())
+    }
+}
@@ -0,0 +1,134 @@
+use std::fmt::Display;
+
+use crate::{generation::Generation, pokemon::iv_to_dv, species::{Stat, StatDistribution}, types::Type};
+
+/// Hidden Power types, in the order they are chosen by IVs.
+pub const HIDDEN_POWER_TYPES: [Type; 16] = [
+    Type::Fighting, Type::Flying, Type::Poison, Type::Ground, Type::Rock, Type::Bug, Type::Ghost, Type::Steel,
+    Type::Fire, Type::Water, Type::Grass, Type::Electric, Type::Psychic, Type::Ice, Type::Dragon, Type::Dark,
+];
+
+/// The order of the stats in a [StatDistribution].
+const STAT_ORDER: [Stat; 6] = [Stat::HP, Stat::Attack, Stat::Defence, Stat::SpecialAttack, Stat::SpecialDefence, Stat::Speed];
+
+/// The order IVs are read in from Gen 3 onwards. Each IV contributes one bit to the type and one bit to the power.
+const IV_ORDER: [Stat; 6] = [Stat::HP, Stat::Attack, Stat::Defence, Stat::Speed, Stat::SpecialAttack, Stat::SpecialDefence];
+
+/// Sums the given bit of each IV, in [IV_ORDER].
+fn iv_bits(ivs: &StatDistribution, bit: u8) -> u16 {
+    IV_ORDER.iter()
+        .enumerate()
+        .map(|(i, stat)| (((ivs.get(*stat) >> bit) & 1) as u16) << i)
+        .sum()
+}
+
+/// Before Gen 3, Hidden Power is decided by DVs, read from the IVs as in [iv_to_dv].
+pub fn hidden_power_type(ivs: &StatDistribution, gen: Generation) -> Type {
+    match gen {
+        Generation::One | Generation::Two => {
+            let attack = iv_to_dv(ivs.attack);
+            let defence = iv_to_dv(ivs.defence);
+            HIDDEN_POWER_TYPES[(4 * (attack % 4) + defence % 4) as usize]
+        },
+        _ => HIDDEN_POWER_TYPES[(iv_bits(ivs, 0) * 15 / 63) as usize]
+    }
+}
+
+/// Hidden Power's base power, which only depends on IVs before Gen 6.
+pub fn hidden_power_power(ivs: &StatDistribution, gen: Generation) -> u8 {
+    match gen {
+        Generation::One | Generation::Two => {
+            let most_significant = |iv: u8| (iv_to_dv(iv) >= 8) as u16;
+            let special = iv_to_dv(ivs.special_attack) as u16;
+            let sum = most_significant(ivs.special_attack) + 2 * most_significant(ivs.speed) + 4 * most_significant(ivs.defence) + 8 * most_significant(ivs.attack);
+            ((5 * sum + special % 4) / 2 + 31) as u8
+        },
+        Generation::Three | Generation::Four | Generation::Five => (iv_bits(ivs, 1) * 40 / 63 + 30) as u8,
+        _ => 60
+    }
+}
+
+/// Every IV spread where each IV is at least `31 - tolerance` that gives Hidden Power the given type.\
+/// Only the bits of each IV that decide the type are chosen, so no spread is thrown away.
+/// Spreads are grouped by those bits, with the group closest to perfect IVs first, and the closest spread first in each group.
+pub fn hidden_power_ivs(type_: Type, tolerance: u8, gen: Generation) -> impl Iterator<Item = StatDistribution> {
+    let lowest = 31 - tolerance.min(31);
+    let choices = |allowed: &dyn Fn(u8) -> bool| (lowest..=31).rev().filter(|iv| allowed(*iv)).collect::<Vec<_>>();
+    let index = HIDDEN_POWER_TYPES.iter().position(|hidden_power_type| *hidden_power_type == type_);
+    let mut groups = match (gen, index) {
+        (_, None) => vec![],
+        // The type comes from the last two bits of the attack and defence DVs
+        (Generation::One | Generation::Two, Some(index)) => {
+            let stat_choices = |stat: Stat| match stat {
+                Stat::Attack => choices(&|iv| iv_to_dv(iv) as usize % 4 == index / 4),
+                Stat::Defence => choices(&|iv| iv_to_dv(iv) as usize % 4 == index % 4),
+                _ => choices(&|_| true),
+            };
+            vec![STAT_ORDER.map(stat_choices)]
+        },
+        // The type comes from the lowest bit of each IV
+        (_, Some(_)) => (0..64u16).filter(|bits| HIDDEN_POWER_TYPES[(bits * 15 / 63) as usize] == type_)
+            .map(|bits| STAT_ORDER.map(|stat| {
+                let bit = IV_ORDER.iter().position(|iv_stat| *iv_stat == stat).expect("Every stat to have an IV") as u16;
+                let odd = (bits >> bit) & 1 == 1;
+                choices(&|iv| (iv % 2 == 1) == odd)
+            }))
+            .collect()
+    };
+    groups.retain(|group| group.iter().all(|choices| !choices.is_empty()));
+    groups.sort_by_key(|group| group.iter().map(|choices| 31 - choices[0]).sum::<u8>());
+    groups.into_iter().flat_map(|group| {
+        let spreads = group.iter().map(Vec::len).product::<usize>();
+        (0..spreads).map(move |mut index| {
+            StatDistribution::from(group.each_ref().map(|choices| {
+                let iv = choices[index % choices.len()];
+                index /= choices.len();
+                iv
+            }))
+        })
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HiddenPowerMismatch {
+    pub claimed: Type,
+    pub actual: Type
+}
+impl Display for HiddenPowerMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Hidden Power is {} but these IVs give {}", self.claimed, self.actual)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{generation::Generation, types::Type};
+
+    use super::{hidden_power_ivs, hidden_power_power, hidden_power_type, HIDDEN_POWER_TYPES};
+
+    #[test]
+    fn hidden_power_tests() {
+        let perfect = [31, 31, 31, 31, 31, 31].into();
+        assert_eq!(hidden_power_type(&perfect, Generation::Nine), Type::Dark);
+        assert_eq!(hidden_power_power(&perfect, Generation::Five), 70);
+        assert_eq!(hidden_power_type(&perfect, Generation::Two), Type::Dark);
+        assert_eq!(hidden_power_power(&perfect, Generation::Two), 70);
+
+        let fire = [31, 30, 31, 30, 31, 30].into();
+        assert_eq!(hidden_power_type(&fire, Generation::Seven), Type::Fire);
+        assert_eq!(hidden_power_power(&fire, Generation::Four), 70);
+
+        let ice = hidden_power_ivs(Type::Ice, 1, Generation::Nine).collect::<Vec<_>>();
+        assert!(ice.contains(&[31, 30, 30, 31, 31, 31].into()));
+        assert!(ice.iter().all(|ivs| hidden_power_type(ivs, Generation::Nine) == Type::Ice));
+        // Spreads closest to perfect IVs come first
+        assert_eq!(ice[0], [31, 31, 31, 31, 31, 30].into());
+
+        // Each IV only has one choice of its lowest bit within a tolerance of 1
+        let every_type = HIDDEN_POWER_TYPES.iter().map(|type_| hidden_power_ivs(*type_, 1, Generation::Nine).count()).sum::<usize>();
+        assert_eq!(every_type, 64);
+        // Only spreads that work are made, so even the largest tolerance is quick
+        assert!(hidden_power_ivs(Type::Dragon, 31, Generation::Two).take(1000).all(|ivs| hidden_power_type(&ivs, Generation::Two) == Type::Dragon));
+        assert_eq!(hidden_power_ivs(Type::Normal, 31, Generation::Nine).next(), None);
+    }
+}
@@ -0,0 +1,185 @@
+use std::collections::BTreeMap;
+
+use crate::damage_calc::{CalcOutcome, DamageRange};
+
+/// The chance of each total damage, for combining damage from several attacks. See [DamageHistogram::convolve].
+#[derive(Clone, Debug, PartialEq)]
+pub struct DamageHistogram(BTreeMap<u32, f64>);
+impl DamageHistogram {
+    /// A histogram where no damage is certain.
+    pub fn new() -> Self {
+        Self(BTreeMap::from([(0, 1.0)]))
+    }
+    /// An empty histogram, to be filled with [Self::add].
+    pub fn empty() -> Self {
+        Self(BTreeMap::new())
+    }
+    pub fn add(&mut self, damage: u32, chance: f64) {
+        *self.0.entry(damage).or_default() += chance;
+    }
+    /// Adds every roll of a damage range, each with an equal share of the given chance.
+    pub fn add_range(&mut self, range: DamageRange, chance: f64) {
+        range.0.into_iter().for_each(|damage| self.add(damage, chance / 16.0));
+    }
+    /// The chance of each total damage from both this and the other, assuming they are independent.
+    pub fn convolve(&self, other: &Self) -> Self {
+        let mut result = Self::empty();
+        for (damage, chance) in self.0.iter() {
+            for (other_damage, other_chance) in other.0.iter() {
+                result.add(damage + other_damage, chance * other_chance);
+            }
+        }
+        result
+    }
+    /// This damage happens with the given chance, and otherwise no damage is dealt. See accuracy.
+    pub fn with_chance(&self, chance: f64) -> Self {
+        let mut result = Self::empty();
+        self.0.iter().for_each(|(damage, damage_chance)| result.add(*damage, damage_chance * chance));
+        result.add(0, 1.0 - chance);
+        result
+    }
+    /// The chance of dealing at least this much damage.
+    pub fn chance_at_least(&self, damage: u32) -> f64 {
+        self.0.range(damage..).map(|(_, chance)| chance).sum()
+    }
+    pub fn mean(&self) -> f64 {
+        self.0.iter().map(|(damage, chance)| *damage as f64 * chance).sum()
+    }
+    /// Each total damage and its chance, in ascending order of damage.
+    pub fn iter(&self) -> impl Iterator<Item = (u32, f64)> + '_ {
+        self.0.iter().map(|(damage, chance)| (*damage, *chance))
+    }
+}
+impl Default for DamageHistogram {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl From<DamageRange> for DamageHistogram {
+    fn from(range: DamageRange) -> Self {
+        let mut histogram = Self::empty();
+        histogram.add_range(range, 1.0);
+        histogram
+    }
+}
+impl From<&CalcOutcome<'_>> for DamageHistogram {
+    /// Weights each roll by the chance of a critical hit and of each base power.
+    fn from(outcome: &CalcOutcome) -> Self {
+        let mut histogram = Self::empty();
+        let crit_chance = outcome.crit_chance() as f64;
+        for power in outcome.power_outcomes() {
+            let chance = power.chance as f64;
+            histogram.add_range(power.damage, chance * (1.0 - crit_chance));
+            histogram.add_range(power.crit_damage, chance * crit_chance);
+        }
+        if outcome.power_outcomes().is_empty() {
+            histogram.add(0, 1.0);
+        }
+        histogram
+    }
+}
+
+/// The total damage of several attacks and chip damage, which can come from different attackers.\
+/// For example, a Rock Slide and a Close Combat after a turn of sandstorm.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DamageSequence(DamageHistogram);
+impl DamageSequence {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// The total damage dealt so far.
+    pub fn histogram(&self) -> &DamageHistogram {
+        &self.0
+    }
+    /// The chance that the total damage knocks out a target on the given HP.
+    pub fn ko_chance(&self, hp: u16) -> f32 {
+        self.0.chance_at_least(hp as u32) as f32
+    }
+    fn then(self, damage: &DamageHistogram) -> Self {
+        Self(self.0.convolve(damage))
+    }
+}
+
+impl DamageSequence {
+    /// Adds an attack, including its chance to crit.
+    pub fn attack(self, outcome: &CalcOutcome) -> Self {
+        self.then(&outcome.histogram())
+    }
+    /// Adds an attack that only lands with the given chance. See accuracy.
+    pub fn attack_with_chance(self, outcome: &CalcOutcome, chance: f32) -> Self {
+        self.then(&outcome.histogram().with_chance(chance as f64))
+    }
+    /// Adds an attack that doesn't crit.
+    pub fn attack_without_crits(self, outcome: &CalcOutcome) -> Self {
+        let mut histogram = DamageHistogram::empty();
+        outcome.power_outcomes().iter().for_each(|power| histogram.add_range(power.damage, power.chance as f64));
+        if histogram.0.is_empty() {
+            return self
+        }
+        self.then(&histogram)
+    }
+    /// Adds a fixed amount of damage. See Stealth Rock and sandstorm.
+    pub fn chip(self, damage: u16) -> Self {
+        self.chip_with_chance(damage, 1.0)
+    }
+    /// Adds a fixed amount of damage that only happens with the given chance. See a burn from Scald.
+    pub fn chip_with_chance(self, damage: u16, chance: f32) -> Self {
+        let mut histogram = DamageHistogram::empty();
+        histogram.add(damage as u32, 1.0);
+        self.then(&histogram.with_chance(chance as f64))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::damage_calc::DamageRange;
+
+    use super::DamageHistogram;
+
+    #[test]
+    fn histogram_tests() {
+        let range = DamageRange([85, 86, 87, 88, 89, 90, 91, 92, 93, 94, 95, 96, 97, 98, 99, 100]);
+        assert_eq!(range.mean(), 92.5);
+        assert_eq!(range.median(), 92.5);
+        assert_eq!(range.variance(), 21.25);
+        assert_eq!(range.showdown_percent(300)[0], 28.3);
+        assert_eq!(range.pixels(300)[0], 14);
+        assert_eq!(range.pixels(100)[15], 48);
+
+        // Two hits of 85-100 are needed for 200 HP
+        let histogram = DamageHistogram::from(range);
+        let two_hits = histogram.convolve(&histogram);
+        assert_eq!(histogram.chance_at_least(100), 1.0 / 16.0);
+        assert_eq!(two_hits.chance_at_least(200), 1.0 / 256.0);
+        // 15 of the 16 rolls knock out 86 HP, a 93.8% chance to KO, and 136 of the 256 pairs of rolls reach 185
+        assert_eq!(histogram.chance_at_least(86), 15.0 / 16.0);
+        assert_eq!(two_hits.chance_at_least(185), 136.0 / 256.0);
+        assert_eq!(two_hits.mean(), 185.0);
+    }
+
+    #[test]
+    #[cfg(feature = "real_data")]
+    fn sequence_tests() -> Result<(), crate::dex::DexError> {
+        use crate::{dex::Dex, field::Field, moves::Weather};
+
+        use super::DamageSequence;
+
+        let dex = Dex::default();
+        let sand = Field::new().weather(Weather::Sandstorm);
+        let target = dex.pokemon("dragonite")?;
+        let rock_slide = dex.calc().attacker(dex.pokemon("tyranitar")?).defender(target).move_("rockslide")?.field(sand).calc(true);
+        let close_combat = dex.calc().attacker(dex.pokemon("urshifu")?).defender(target).move_("closecombat")?.field(sand).calc(true);
+        let hp = target.max_hp();
+        let sand_chip = -target.residual(&sand, &sand.defender_side).total() as u16;
+
+        let alone = DamageSequence::new().attack(&close_combat);
+        let together = alone.clone().attack(&rock_slide);
+        let after_sand = together.clone().chip(sand_chip);
+        assert!(alone.ko_chance(hp) < together.ko_chance(hp));
+        assert!(sand_chip > 0 && together.ko_chance(hp) < after_sand.ko_chance(hp));
+
+        let missed = together.clone().attack_with_chance(&rock_slide, 0.0);
+        assert_eq!(missed.ko_chance(hp), together.ko_chance(hp));
+        Ok(())
+    }
+}
@@ -1,6 +1,6 @@
 use serde::Deserialize;
 
-use crate::{generation::Generation, moves::{BoostsList, Condition, NonStandardReason, Status, VolatileStatus}, parsing_utils::{impl_try_from_either, Either, NotImplemented}, types::Type};
+use crate::{generation::Generation, moves::{BoostsList, Condition, NonStandardReason, Status, VolatileStatus}, names::IdentifierName, parsing_utils::{impl_try_from_either, Either, NotImplemented}, species::Species, types::Type};
 
 fn some_true() -> Option<bool> {
     Some(true)
@@ -66,6 +66,30 @@ pub struct ItemData {
     #[serde(flatten)]
     pub priorities: Priorities,
 }
+impl ItemData {
+    /// The identifier this item is stored under in the [Dex](crate::dex::Dex). See Heavy-Duty Boots -> heavydutyboots.
+    pub fn id(&self) -> String {
+        IdentifierName::new(self.name.clone()).inner().to_string()
+    }
+
+    /// Whether this item can be knocked off or stolen from `holder`.
+    /// Z-Crystals and Mega Stones never can; neither can the items that decide a forme of their user,
+    /// like Plates on Arceus or Rusted Sword on Zacian, or Booster Energy on a Paradox pokemon.
+    pub fn can_be_removed_from(&self, holder: &Species) -> bool {
+        if self.z_move.is_some() || self.mega_stone.is_some() || self.override_innate_stealable == Some(false) {
+            return false;
+        }
+        let id = self.id();
+        if id == "boosterenergy" {
+            return !holder.tags.iter().any(|tag| tag == "Paradox");
+        }
+        let base_species = |name: &str| name.split('-').next().unwrap_or(name).to_string();
+        let holder_base = holder.base_species.clone().unwrap_or_else(|| holder.name.clone());
+        let decides_forme = self.forced_forme.is_some() || matches!(id.as_str(), "griseousorb" | "rustedsword" | "rustedshield" | "blueorb" | "redorb");
+        let belongs_to_holder = self.forced_forme.iter().chain(self.item_users.iter().flatten()).any(|user| base_species(user) == holder_base);
+        !(decides_forme && belongs_to_holder)
+    }
+}
 
 #[derive(Deserialize, Debug, Clone, Copy)]
 #[serde(rename_all = "camelCase")]
@@ -139,6 +163,14 @@ pub struct Priorities {
     pub on_try_boost_priority: Option<i8>,
     pub on_try_move_priority: Option<i8>,
     pub on_base_power_priority: Option<i8>,
+    pub on_any_base_power_priority: Option<i8>,
+    pub on_modify_crit_ratio_priority: Option<i8>,
+    pub on_after_move_self_priority: Option<i8>,
+    pub on_foe_modify_def_priority: Option<i8>,
+    #[serde(rename = "onFoeModifySpDPriority")]
+    pub on_foe_modify_spd_priority: Option<i8>,
+    pub on_modify_weight_priority: Option<i8>,
+    pub on_switch_in_priority: Option<i8>,
     pub on_after_move_secondary_priority: Option<i8>,
     pub on_after_move_secondary_self_priority: Option<i8>,
     pub on_after_set_status_priority: Option<i8>,
@@ -1,14 +1,14 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, fmt::Display};
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-use crate::{generation::Generation, names::moves::Move, natures::Nature, parsing_utils::{Either, NotImplemented}, species::StatDistribution};
+use crate::{dex::Dex, generation::Generation, moves::{MoveData, NonStandardReason}, names::moves::Move, natures::Nature, parsing_utils::{Either, NotImplemented}, pokemon::MAX_MOVES, species::{Species, StatDistribution}};
 
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 #[serde(deny_unknown_fields)]
 pub struct Learnset {
-    pub learnset: Option<HashMap<Move, Vec<String>>>,
+    pub learnset: Option<HashMap<Move, Vec<LearnSource>>>,
     pub event_data: Option<Vec<EncounterData>>,
     #[serde(default)]
     pub encounters: Vec<EncounterData>,
@@ -21,6 +21,259 @@ impl Learnset {
             .as_ref()
             .map_or_else(Vec::new, |learnset| learnset.keys().collect())
     }
+    /// Every way a move can be learnt, in any generation.
+    pub fn sources(&self, move_: Move) -> &[LearnSource] {
+        self.learnset.as_ref().and_then(|learnset| learnset.get(&move_)).map_or(&[], Vec::as_slice)
+    }
+    /// Whether a move can be learnt in or before the given generation, and then transferred.
+    pub fn can_learn(&self, move_: Move, gen: Generation) -> bool {
+        self.sources(move_).iter().any(|source| source.gen <= gen)
+    }
+    /// The ways a move can be learnt in the given generation.
+    pub fn methods(&self, move_: Move, gen: Generation) -> Vec<LearnMethod> {
+        self.sources(move_).iter().filter(|source| source.gen == gen).map(|source| source.method).collect()
+    }
+    /// The lowest level this move is learnt by levelling up in the given generation. 0 is on evolution in Gen 7 on.
+    pub fn level(&self, move_: Move, gen: Generation) -> Option<u8> {
+        self.methods(move_, gen).into_iter()
+            .filter_map(|method| match method {
+                LearnMethod::LevelUp(level) => Some(level),
+                _ => None
+            })
+            .min()
+    }
+    /// The event a source refers to. See [LearnMethod::Event].
+    pub fn event(&self, source: LearnSource) -> Option<&EncounterData> {
+        match source.method {
+            LearnMethod::Event(index) => self.event_data.as_ref()?.get(index),
+            _ => None
+        }
+    }
+}
+
+/// How a move is learnt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LearnMethod {
+    /// Levelling up to the given level, or when first caught or evolved.
+    LevelUp(u8),
+    /// A TM, HM or TR.
+    Machine,
+    Tutor,
+    Egg,
+    /// The event at this index in [Learnset::event_data].
+    Event(usize),
+    /// Only in Gen 5.
+    DreamWorld,
+    /// Transferred from the Virtual Console or Let's Go, in Gen 7 and 8.
+    VirtualConsole,
+    /// Learnt some other way, such as Rotom's formes changing their move.
+    Restricted,
+    /// Not a real source. Showdown uses it for chain breeding in Gen 3 and 4.
+    ChainBreed,
+}
+
+/// A way to learn a move in a generation. Showdown writes these as codes like "8L15" and "6S2".
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(try_from = "String")]
+pub struct LearnSource {
+    pub gen: Generation,
+    pub method: LearnMethod,
+}
+
+#[derive(Debug)]
+pub struct NotALearnSource(String);
+impl Display for NotALearnSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "'{}' is not a learnset source", self.0)
+    }
+}
+impl TryFrom<&str> for LearnSource {
+    type Error = NotALearnSource;
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let error = || NotALearnSource(value.to_string());
+        let gen = value.get(..1).and_then(|gen| Generation::try_from(gen).ok()).ok_or_else(error)?;
+        let method = value.get(1..2).ok_or_else(error)?;
+        let number = value.get(2..).unwrap_or_default();
+        let method = match method {
+            "L" => LearnMethod::LevelUp(number.parse().map_err(|_| error())?),
+            "S" => LearnMethod::Event(number.parse().map_err(|_| error())?),
+            "M" => LearnMethod::Machine,
+            "T" => LearnMethod::Tutor,
+            "E" => LearnMethod::Egg,
+            "D" => LearnMethod::DreamWorld,
+            "V" => LearnMethod::VirtualConsole,
+            "R" => LearnMethod::Restricted,
+            "C" => LearnMethod::ChainBreed,
+            _ => return Err(error())
+        };
+        Ok(Self { gen, method })
+    }
+}
+impl TryFrom<String> for LearnSource {
+    type Error = NotALearnSource;
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::try_from(value.as_str())
+    }
+}
+impl Display for LearnSource {
+    /// Writes Showdown's code for this source.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let gen = self.gen as u8 + 1;
+        match self.method {
+            LearnMethod::LevelUp(level) => write!(f, "{gen}L{level}"),
+            LearnMethod::Event(index) => write!(f, "{gen}S{index}"),
+            LearnMethod::Machine => write!(f, "{gen}M"),
+            LearnMethod::Tutor => write!(f, "{gen}T"),
+            LearnMethod::Egg => write!(f, "{gen}E"),
+            LearnMethod::DreamWorld => write!(f, "{gen}D"),
+            LearnMethod::VirtualConsole => write!(f, "{gen}V"),
+            LearnMethod::Restricted => write!(f, "{gen}R"),
+            LearnMethod::ChainBreed => write!(f, "{gen}C"),
+        }
+    }
+}
+
+/// A way to learn a move, and the species whose learnset it comes from. See [Dex::resolved_learnset].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InheritedSource<'a> {
+    pub source: LearnSource,
+    /// The name of the species that learns the move this way. See Pichu for Pikachu's egg moves.
+    pub species: &'a str,
+}
+
+/// Every move a species can learn, including as a prevolution.
+#[derive(Debug, Clone, Default)]
+pub struct ResolvedLearnset<'a>(pub HashMap<Move, Vec<InheritedSource<'a>>>);
+impl<'a> ResolvedLearnset<'a> {
+    pub fn all_moves(&self) -> Vec<&Move> {
+        self.0.keys().collect()
+    }
+    pub fn sources(&self, move_: Move) -> &[InheritedSource<'a>] {
+        self.0.get(&move_).map_or(&[], Vec::as_slice)
+    }
+    /// Whether a move can be learnt in or before the given generation, by this species or a prevolution.
+    pub fn can_learn(&self, move_: Move, gen: Generation) -> bool {
+        self.sources(move_).iter().any(|source| source.source.gen <= gen)
+    }
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl Dex {
+    /// The learnset of a species, or of the forme it comes from if it doesn't have its own. See Rotom-Wash and Charizard-Mega-X.\
+    /// Formes with their own learnset don't use their base species', see Raichu-Alola.
+    pub(crate) fn own_learnset<'a>(&'a self, species: &'a Species) -> Option<(&'a str, &'a Learnset)> {
+        [Some(&species.name), species.changes_from.as_ref(), species.base_species.as_ref()].into_iter()
+            .flatten()
+            .find_map(|name| self.learnset(name).ok().filter(|learnset| learnset.learnset.is_some()).map(|learnset| (name.as_str(), learnset)))
+    }
+    /// A species and each of its prevolutions that are in this generation, starting with the species.
+    pub(crate) fn evolution_line<'a>(&'a self, species: &'a Species) -> Vec<&'a Species> {
+        let mut line = vec![species];
+        while let Some(prevo) = line.last().and_then(|species| species.prevo.as_ref())
+            .and_then(|prevo| self.species(prevo).ok())
+            .filter(|prevo| prevo.is_nonstandard != Some(NonStandardReason::Future)) {
+            line.push(prevo);
+        }
+        line
+    }
+    /// Every move a species can learn, merged with the moves of its prevolutions that are in this generation.\
+    /// Empty if none of them have a learnset.
+    pub fn resolved_learnset<'a>(&'a self, species: &'a Species) -> ResolvedLearnset<'a> {
+        let mut resolved = ResolvedLearnset::default();
+        for species in self.evolution_line(species) {
+            let Some((name, learnset)) = self.own_learnset(species) else {
+                continue
+            };
+            for (move_, sources) in learnset.learnset.iter().flatten() {
+                let inherited = sources.iter().map(|source| InheritedSource { source: *source, species: name });
+                resolved.0.entry(*move_).or_default().extend(inherited);
+            }
+        }
+        resolved
+    }
+}
+
+impl Dex {
+    /// The moves a species learns by levelling up in this generation, and the levels it learns them at.
+    fn level_up_moves(&self, species: &Species) -> Vec<(u8, Move)> {
+        let Some((_, learnset)) = self.own_learnset(species) else {
+            return vec![]
+        };
+        learnset.learnset.iter().flatten()
+            .flat_map(|(move_, sources)| sources.iter().map(move |source| (*move_, source)))
+            .filter(|(_, source)| source.gen == self.generation())
+            .filter_map(|(move_, source)| match source.method {
+                LearnMethod::LevelUp(level) => Some((level, move_)),
+                _ => None
+            })
+            .collect()
+    }
+    /// The level a species evolves from its prevolution at, if it evolves by level. Evolving by knowing a move, see Yanmega,
+    /// happens the level after the prevolution learns it.
+    fn evolution_level(&self, species: &Species, prevo: &Species) -> Option<u8> {
+        if let Some(level) = species.evo_level {
+            return Some(level)
+        }
+        let evo_move = self.move_(species.evo_move.as_ref()?).ok()?.id()?;
+        self.level_up_moves(prevo).into_iter()
+            .filter(|(_, move_)| *move_ == evo_move)
+            .map(|(level, _)| level.saturating_add(1))
+            .min()
+    }
+
+    /// The moves a wild or in-game pokemon knows at the given level: the last four it learnt by levelling up in this generation.\
+    /// It is assumed to have been its prevolutions until it could evolve, learning their moves on the way, and its evolution moves when it evolved.
+    /// Evolutions by item, trade or friendship have no level, so they are assumed to have happened at the given level. See Raichu.
+    /// Moves learnt at the same level are learnt in order of move number.
+    pub fn level_up_moveset<'a>(&'a self, species: &'a Species, level: u8) -> Vec<&'a MoveData> {
+        // Each species in the evolution line, and the level it was reached at
+        let mut stages = vec![];
+        let mut current = species;
+        let mut last_level = level;
+        loop {
+            let prevo = current.prevo.as_ref()
+                .and_then(|prevo| self.species(prevo).ok())
+                .filter(|prevo| prevo.is_nonstandard != Some(NonStandardReason::Future));
+            let evolution = prevo.map(|prevo| (prevo, self.evolution_level(current, prevo).unwrap_or(last_level)))
+                .filter(|(_, evolution_level)| *evolution_level <= last_level);
+            match evolution {
+                Some((prevo, evolution_level)) => {
+                    stages.push((current, Some(evolution_level)));
+                    current = prevo;
+                    last_level = evolution_level;
+                },
+                None => {
+                    stages.push((current, None));
+                    break
+                }
+            }
+        }
+        stages.reverse();
+
+        let mut moveset: Vec<&MoveData> = vec![];
+        for (i, (species, evolved_at)) in stages.iter().enumerate() {
+            let start = evolved_at.unwrap_or(1);
+            // Moves at the level the next stage evolves at are learnt after evolving
+            let end = stages.get(i + 1).and_then(|(_, evolved_at)| *evolved_at).unwrap_or(level.saturating_add(1));
+            let mut learnt = self.level_up_moves(species).into_iter()
+                .filter(|(move_level, _)| (start..end).contains(move_level) || (evolved_at.is_some() && *move_level == 0))
+                .filter_map(|(move_level, move_)| Some((move_level, self.move_(move_).ok()?)))
+                .collect::<Vec<_>>();
+            learnt.sort_by_key(|(move_level, move_)| (*move_level, move_.num));
+            for (_, move_) in learnt {
+                if moveset.iter().any(|known| known.name == move_.name) {
+                    continue
+                }
+                if moveset.len() == MAX_MOVES {
+                    moveset.remove(0);
+                }
+                moveset.push(move_);
+            }
+        }
+        moveset
+    }
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -40,12 +293,13 @@ pub struct EncounterData {
     pub emerald_event_egg: bool,
     #[serde(default)]
     pub is_hidden:bool,
-    pub ivs: Option<StatDistribution>,
+    /// IVs that are fixed. Stats that aren't listed can be anything.
+    pub ivs: Option<StatDistribution<Option<u8>>>,
     #[serde(rename = "perfectIVs")]
     pub perfect_ivs: Option<u8>
 }
 
-#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Gender {
     #[serde(rename = "F")]
     Female,
@@ -72,4 +326,99 @@ impl TryFrom<Option<Either<bool, u8>>> for EventShiny {
             Some(Either::B(_)) => Err(NotImplemented("Only 'true', 'false' or '1' are valid for shiny"))
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use crate::{generation::Generation, names::moves::Move};
+
+    use super::{LearnMethod, LearnSource, Learnset};
+
+    #[test]
+    fn learn_source_tests() {
+        let learnset: Learnset = serde_json::from_str(r#"{
+            "learnset": {"earthquake": ["9M", "8L15", "8M", "4T"], "outrage": ["7E", "6S0"]},
+            "eventData": [{"generation": 6, "level": 50, "moves": ["outrage"]}]
+        }"#).unwrap();
+        assert!(learnset.can_learn(Move::Earthquake, Generation::Four));
+        assert!(!learnset.can_learn(Move::Outrage, Generation::Five));
+        assert!(!learnset.can_learn(Move::Spikes, Generation::Nine));
+        assert_eq!(learnset.methods(Move::Earthquake, Generation::Eight), [LearnMethod::LevelUp(15), LearnMethod::Machine]);
+        assert_eq!(learnset.level(Move::Earthquake, Generation::Eight), Some(15));
+        assert_eq!(learnset.level(Move::Earthquake, Generation::Nine), None);
+
+        let event = learnset.sources(Move::Outrage)[1];
+        assert_eq!(event.method, LearnMethod::Event(0));
+        assert_eq!(learnset.event(event).and_then(|event| event.level), Some(50));
+        assert_eq!(event.to_string(), "6S0");
+        assert!(LearnSource::try_from("9X").is_err());
+    }
+
+    /// A Dex with only the given species and moves from the real data, and the given learnsets.
+    #[cfg(feature = "real_data")]
+    fn test_dex(species: &[&str], moves: &[&str], learnsets: &str) -> crate::dex::Dex {
+        use crate::dex::Dex;
+
+        let data = Dex::default();
+        let species = species.iter().map(|id| (id.to_string(), data.species(id).unwrap().clone())).collect();
+        let moves = moves.iter().map(|id| (id.to_string(), data.move_(id).unwrap().clone())).collect();
+        let learnsets = serde_json::from_str(learnsets).unwrap();
+        Dex::new(Generation::Nine, moves, species, Default::default(), learnsets, Default::default(), Default::default())
+    }
+
+    #[test]
+    #[cfg(feature = "real_data")]
+    fn resolved_learnset_tests() {
+        let dex = test_dex(&["pichu", "pikachu", "raichu", "raichualola", "rotom", "rotomwash"], &[], r#"{
+            "pichu": {"learnset": {"volttackle": ["9E"], "charm": ["9L1"]}},
+            "pikachu": {"learnset": {"thunderbolt": ["9M"]}},
+            "raichu": {"learnset": {"thunderpunch": ["9M"]}},
+            "raichualola": {"learnset": {"psychic": ["9M"]}},
+            "rotom": {"learnset": {"thunderbolt": ["9M"]}},
+            "rotomwash": {}
+        }"#);
+
+        let raichu = dex.resolved_learnset(dex.species("raichu").unwrap());
+        assert!(raichu.can_learn(Move::VoltTackle, Generation::Nine));
+        assert_eq!(raichu.sources(Move::VoltTackle)[0].species, "Pichu");
+        assert_eq!(raichu.sources(Move::ThunderPunch)[0].species, "Raichu");
+
+        // Regional formes have their own learnset, but still inherit from their prevolutions
+        let alola = dex.resolved_learnset(dex.species("raichualola").unwrap());
+        assert!(alola.can_learn(Move::Psychic, Generation::Nine) && alola.can_learn(Move::Charm, Generation::Nine));
+        assert!(!alola.can_learn(Move::ThunderPunch, Generation::Nine));
+
+        // Formes without a learnset use their base species'
+        let wash = dex.resolved_learnset(dex.species("rotomwash").unwrap());
+        assert_eq!(wash.sources(Move::Thunderbolt)[0].species, "Rotom");
+    }
+
+    #[test]
+    #[cfg(feature = "real_data")]
+    fn level_up_moveset_tests() {
+        let moves = ["scratch", "growl", "ember", "smokescreen", "dragonbreath", "firefang", "slash", "airslash", "tackle", "ancientpower", "bugbuzz",
+            "thundershock", "quickattack", "thunderbolt", "thunderpunch"];
+        let dex = test_dex(&["charmander", "charmeleon", "charizard", "yanma", "yanmega", "pikachu", "raichu"], &moves, r#"{
+            "charmander": {"learnset": {"scratch": ["9L1"], "growl": ["9L1"], "ember": ["9L4"], "smokescreen": ["9L8"], "dragonbreath": ["9L12"], "firefang": ["9L17"]}},
+            "charmeleon": {"learnset": {"scratch": ["9L1"], "growl": ["9L1"], "ember": ["9L1"], "smokescreen": ["9L1"], "dragonbreath": ["9L12"], "firefang": ["9L19"], "slash": ["9L30"]}},
+            "charizard": {"learnset": {"airslash": ["9L0"], "scratch": ["9L1"], "dragonbreath": ["9L12"], "firefang": ["9L19"], "slash": ["9L30"]}},
+            "yanma": {"learnset": {"tackle": ["9L1"], "ancientpower": ["9L33"]}},
+            "yanmega": {"learnset": {"tackle": ["9L1"], "bugbuzz": ["9L1"], "airslash": ["9L46"]}},
+            "pikachu": {"learnset": {"thundershock": ["9L1"], "quickattack": ["9L6"], "thunderbolt": ["9L36"]}},
+            "raichu": {"learnset": {"thunderpunch": ["9L0"], "thundershock": ["9L1"]}}
+        }"#);
+        let moveset = |species: &str, level: u8| dex.level_up_moveset(dex.species(species).unwrap(), level)
+            .into_iter().map(|move_| move_.name.clone()).collect::<Vec<_>>();
+
+        // Charmander's moves, then Charmeleon's from 16, then Charizard's evolution move
+        assert_eq!(moveset("charizard", 36), ["Dragon Breath", "Fire Fang", "Slash", "Air Slash"]);
+        assert_eq!(moveset("charmander", 5), ["Scratch", "Growl", "Ember"]);
+        // Too low to have evolved, so it learnt Charmeleon's moves from level 1
+        assert_eq!(moveset("charmeleon", 15), ["Growl", "Ember", "Smokescreen", "Dragon Breath"]);
+        // Yanma evolves after learning Ancient Power at 33
+        assert_eq!(moveset("yanmega", 50), ["Tackle", "Ancient Power", "Air Slash"]);
+        // Raichu evolves by Thunder Stone, so it was a Pikachu until the given level
+        assert_eq!(moveset("raichu", 40), ["Thunder Shock", "Quick Attack", "Thunderbolt", "Thunder Punch"]);
+        assert_eq!(moveset("raichu", 20), ["Thunder Shock", "Quick Attack", "Thunder Punch"]);
+        assert_eq!(moveset("charmander", 255).len(), 4);
+    }
+}
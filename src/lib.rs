@@ -7,7 +7,21 @@ pub mod generation;
 pub mod natures;
 pub mod pokemon;
 pub mod damage_calc;
+pub mod histogram;
 pub mod items;
+pub mod field;
+pub mod enum_set;
+pub mod hidden_power;
+pub mod residual;
+pub mod hazards;
+pub mod battle_context;
+pub mod doubles;
+pub mod paste;
+pub mod packed;
+pub mod team_sheet;
+pub mod pokemon_set;
+pub mod validator;
+pub mod encounters;
 
 pub mod names;
 
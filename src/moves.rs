@@ -2,7 +2,7 @@ use std::collections::HashMap;
 
 use serde::{Deserialize, Serialize};
 
-use crate::{items::Priorities, parsing_utils::{deserialize_via, impl_from_either, impl_try_from_either, Either, NotImplemented}, species::Stat, types::Type};
+use crate::{items::Priorities, names::{moves::Move, IdentifierName}, parsing_utils::{deserialize_via, impl_from_either, impl_try_from_either, Either, NotImplemented}, species::Stat, types::Type};
 
 #[derive(Deserialize, Clone)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
@@ -118,6 +118,11 @@ pub struct MoveData {
     /// Whether the move checks for accuracy between each hit. See Population Bomb.
     #[serde(default)]
     pub multiaccuracy: bool,
+    /// Whether the move skips the random damage roll. See Gen 2 Flail.
+    #[serde(default)]
+    pub no_damage_variance: bool,
+    /// `Some(false)` if the move always fails. See Teleport before Gen 8.
+    pub on_try: Option<bool>,
 
     #[serde(deserialize_with = "deserialize_via::<_, Option<BoostsList>, _SelfBoost>")]
     #[serde(default)]
@@ -135,6 +140,10 @@ impl MoveData {
     pub fn has_flag(&self, flag: Flag) -> bool {
         self.flags.has_flag(flag)
     }
+    /// `None` if this move has no [Move] name. See 10,000,000 Volt Thunderbolt.
+    pub fn id(&self) -> Option<Move> {
+        Move::try_from(IdentifierName::new(self.name.clone())).ok()
+    }
 }
 
 impl From<Either<Option<Secondary>, Vec<Secondary>>> for Option<Vec<Secondary>> {
@@ -454,6 +463,32 @@ pub struct BoostsList {
     #[serde(default)]
     pub accuracy: i8,    
 }
+impl BoostsList {
+    pub fn get(&self, stat: Stat) -> i8 {
+        match stat {
+            Stat::HP => self.hp,
+            Stat::Attack => self.attack,
+            Stat::Defence => self.defence,
+            Stat::SpecialAttack => self.special_attack,
+            Stat::SpecialDefence => self.special_defence,
+            Stat::Speed => self.speed,
+            Stat::Accuracy => self.accuracy,
+            Stat::Evasion => self.evasion,
+        }
+    }
+    pub fn get_mut(&mut self, stat: Stat) -> &mut i8 {
+        match stat {
+            Stat::HP => &mut self.hp,
+            Stat::Attack => &mut self.attack,
+            Stat::Defence => &mut self.defence,
+            Stat::SpecialAttack => &mut self.special_attack,
+            Stat::SpecialDefence => &mut self.special_defence,
+            Stat::Speed => &mut self.speed,
+            Stat::Accuracy => &mut self.accuracy,
+            Stat::Evasion => &mut self.evasion,
+        }
+    }
+}
 
 #[derive(Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
 #[serde(rename_all = "camelCase")]
@@ -570,8 +605,19 @@ pub enum VolatileStatus {
     SaltCure,
     SparklingAria,
     SyrupBomb,
+    Dynamax,
+
+    // The semi-invulnerable turn of two turn moves
+    Fly,
+    Bounce,
+    Dig,
+    Dive,
+    PhantomForce,
+    ShadowForce,
+    SkyDrop,
 
     // Past Volatile Statuses
+    PartialTrappingLock,
     MudSport,
     Autotomize,
     WaterSport,
@@ -598,6 +644,12 @@ pub enum SideCondition {
     QuickGuard,
     StealthRock,
     LightScreen,
+    /// The Sea of Fire from Fire Pledge and Grass Pledge.
+    FirePledge,
+    GMaxWildfire,
+    GMaxCannonade,
+    GMaxVineLash,
+    GMaxVolcalith,
 }
 
 #[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
@@ -14,6 +14,30 @@ impl TryFrom<IdentifierName> for Ability {
     }
 }
 
+impl Ability {
+    /// The display name Showdown uses, such as "Mind's Eye".\
+    /// Without real data this spaces out the variant name instead, giving "Minds Eye" or "R K S System",
+    /// which Showdown and [Ability::try_from] still recognise.
+    pub fn name(&self) -> String {
+        if *self == Self::NoAbility {
+            return String::new()
+        }
+        let variant = format!("{self:?}");
+        #[cfg(feature = "real_data")]
+        if let Some(name) = crate::real_data::ability_names().get(&variant.to_lowercase()) {
+            return name.clone()
+        }
+        let mut name = String::new();
+        for ch in variant.chars() {
+            if ch.is_ascii_uppercase() && !name.is_empty() {
+                name.push(' ');
+            }
+            name.push(ch);
+        }
+        name
+    }
+}
+
 #[derive(Deserialize, Clone, Copy, PartialEq, Eq, EnumString, Debug)]
 #[serde(rename_all = "lowercase", try_from = "IdentifierName")]
 #[strum(serialize_all = "lowercase", use_phf)]
@@ -30,7 +30,6 @@ impl TryFrom<IdentifierName> for Move {
 #[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Eq, EnumString, Debug, Hash)]
 #[serde(rename_all = "lowercase", try_from = "IdentifierName")]
 #[strum(serialize_all = "lowercase", use_phf)]
-
 pub enum Move {
     #[serde(rename = "10000000voltthunderbolt")]
     TenMillionVoltThunderbolt,
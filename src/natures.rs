@@ -6,7 +6,7 @@ use serde_variant::to_variant_name;
 use crate::{dex::Identifier, species::Stat};
 
 
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Nature {
     Hardy,
     Lonely,
@@ -0,0 +1,225 @@
+use crate::{dex::{Dex, DexError}, learnsets::Gender, names::{abilities::Ability, IdentifierName}, paste::{gender_letter, parse_type, to_id, PasteError, PasteErrorReason, TeamMember, MAX_TEAM_SIZE, STAT_NAMES}, pokemon::MAX_MOVES, species::StatDistribution};
+
+/// The number of `|` separated fields in a packed pokemon. The last one also holds the comma separated extras.
+const PACKED_FIELDS: usize = 12;
+
+impl Dex {
+    /// Reads a single pokemon in Showdown's packed format. `position` is its position in the team, for errors.
+    pub fn unpack_pokemon(&self, text: &str, position: usize) -> Result<TeamMember<'_>, PasteError> {
+        let error = |token: &str, reason: PasteErrorReason| PasteError { line: position, token: token.to_string(), reason };
+        let not_found = |token: &str, dex_error: DexError| error(token, PasteErrorReason::NotFound(dex_error));
+        let number = |token: &str| token.parse::<u8>().map_err(|_| error(token, PasteErrorReason::InvalidNumber));
+
+        let fields = text.split('|').collect::<Vec<_>>();
+        let [name, species, item, ability, moves, nature, evs, gender, ivs, shiny, level, extras] = fields[..] else {
+            return Err(error(text, PasteErrorReason::MissingFields))
+        };
+
+        // The species is left out when it's the same as the name
+        let (nickname, species) = match species {
+            "" => (None, name),
+            species => (Some(name.to_string()), species),
+        };
+        let mut pokemon = self.pokemon(to_id(species)).map_err(|dex_error| not_found(species, dex_error))?.level(100);
+        if !item.is_empty() {
+            pokemon = pokemon.item(self.item(to_id(item)).map_err(|dex_error| not_found(item, dex_error))?);
+        }
+        // Abilities can also be given by their slot on the species, and an empty ability is the first slot
+        let abilities = &pokemon.species.abilities;
+        let slot = match ability {
+            "0" => Some(abilities.normal.first().copied()),
+            "1" => Some(abilities.normal.get(1).copied()),
+            "H" => Some(abilities.hidden),
+            "S" => Some(abilities.special),
+            _ => None,
+        };
+        pokemon.ability = match (ability, slot) {
+            ("", _) => pokemon.ability,
+            (_, Some(slot)) => slot.ok_or_else(|| not_found(ability, DexError::NotFound(ability.to_string())))?,
+            (ability, None) => Ability::try_from(IdentifierName::new(to_id(ability))).map_err(|dex_error| not_found(ability, dex_error))?,
+        };
+        if !nature.is_empty() {
+            pokemon = pokemon.nature(self.nature(to_id(nature)).map_err(|dex_error| not_found(nature, dex_error))?);
+        }
+        pokemon.evs = unpack_stats(evs, 0, &error)?;
+        pokemon.ivs = unpack_stats(ivs, 31, &error)?;
+        if !level.is_empty() {
+            pokemon = pokemon.level(number(level)?);
+        }
+
+        let mut extras = extras.split(',').map(|extra| Some(extra).filter(|extra| !extra.is_empty()));
+        if let Some(happiness) = extras.next().flatten() {
+            pokemon = pokemon.happiness(number(happiness)?);
+        }
+        pokemon.shiny = shiny == "S";
+        pokemon.gender = match gender {
+            "" => None,
+            "M" => Some(Gender::Male),
+            "F" => Some(Gender::Female),
+            "N" => Some(Gender::Unknown),
+            gender => return Err(error(gender, PasteErrorReason::UnknownGender)),
+        };
+        for move_ in moves.split(',').filter(|move_| !move_.is_empty()) {
+            if pokemon.moves().count() == MAX_MOVES {
+                return Err(error(move_, PasteErrorReason::TooManyMoves))
+            }
+            pokemon = pokemon.move_(self.move_(to_id(move_)).map_err(|dex_error| not_found(move_, dex_error))?);
+        }
+        let mut member = TeamMember { nickname, ..TeamMember::new(pokemon) };
+
+        // Pokeball, Hidden Power type, Gigantamax, Dynamax Level, Tera Type
+        member.pokeball = extras.next().flatten().map(str::to_string);
+        if let Some(hidden_power_type) = extras.next().flatten() {
+            member.hidden_power_type = Some(parse_type(hidden_power_type).ok_or_else(|| error(hidden_power_type, PasteErrorReason::UnknownType))?);
+        }
+        member.pokemon.gigantamax = extras.next().flatten() == Some("G");
+        if let Some(dynamax_level) = extras.next().flatten() {
+            member.dynamax_level = number(dynamax_level)?;
+        }
+        if let Some(tera_type) = extras.next().flatten() {
+            member.pokemon.tera_type = Some(parse_type(tera_type).ok_or_else(|| error(tera_type, PasteErrorReason::UnknownType))?);
+        }
+        Ok(member)
+    }
+
+    /// Reads a team in Showdown's packed format, where each pokemon is separated by `]`.
+    pub fn unpack_team(&self, text: &str) -> Result<Vec<TeamMember<'_>>, PasteError> {
+        let sets = text.trim().split(']').filter(|set| !set.is_empty()).collect::<Vec<_>>();
+        if let Some(extra) = sets.get(MAX_TEAM_SIZE) {
+            return Err(PasteError { line: MAX_TEAM_SIZE + 1, token: extra.to_string(), reason: PasteErrorReason::TooManyPokemon })
+        }
+        sets.into_iter().enumerate().map(|(i, set)| self.unpack_pokemon(set, i + 1)).collect()
+    }
+}
+
+/// Reads packed EVs or IVs like "252,,4,,,252". Empty stats are `default`, and so is every stat if `text` is empty.
+fn unpack_stats(text: &str, default: u8, error: &impl Fn(&str, PasteErrorReason) -> PasteError) -> Result<StatDistribution, PasteError> {
+    let mut stats = StatDistribution::from([default; 6]);
+    if text.is_empty() {
+        return Ok(stats)
+    }
+    let values = text.split(',').collect::<Vec<_>>();
+    if values.len() != STAT_NAMES.len() {
+        return Err(error(text, PasteErrorReason::MissingFields))
+    }
+    for ((stat, _), value) in STAT_NAMES.iter().zip(values) {
+        if !value.is_empty() {
+            *stats.get_mut(*stat) = value.parse().map_err(|_| error(value, PasteErrorReason::InvalidNumber))?;
+        }
+    }
+    Ok(stats)
+}
+
+/// Writes EVs or IVs in the packed format, leaving out stats that are `default`.
+fn pack_stats(stats: &StatDistribution, default: u8) -> String {
+    let packed = STAT_NAMES.iter()
+        .map(|(stat, _)| stats.get(*stat))
+        .map(|value| if value == default {String::new()} else {value.to_string()})
+        .collect::<Vec<_>>()
+        .join(",");
+    if packed == ",,,,," {String::new()} else {packed}
+}
+
+impl TeamMember<'_> {
+    /// Writes this pokemon in Showdown's packed format.
+    pub fn pack(&self) -> String {
+        let pokemon = &self.pokemon;
+        let species = to_id(&pokemon.species.name);
+        let name = self.nickname.clone().unwrap_or_else(|| pokemon.species.name.clone());
+        let ability = match pokemon.ability {
+            Ability::NoAbility => String::new(),
+            ability => to_id(&ability.name()),
+        };
+        let optional = |value: Option<String>| value.unwrap_or_default();
+        let mut fields = vec![
+            name.clone(),
+            if to_id(&name) == species {String::new()} else {species},
+            optional(pokemon.item.map(|item| item.id())),
+            ability,
+            pokemon.moves().map(|slot| to_id(&slot.move_.name)).collect::<Vec<_>>().join(","),
+            optional(pokemon.nature.map(|nature| nature.name.clone())),
+            pack_stats(&pokemon.evs, 0),
+            optional(pokemon.gender.map(|gender| gender_letter(gender).to_string())),
+            pack_stats(&pokemon.ivs, 31),
+            if pokemon.shiny {"S".to_string()} else {String::new()},
+            if pokemon.level == 100 {String::new()} else {pokemon.level.to_string()},
+            if pokemon.happiness == 255 {String::new()} else {pokemon.happiness.to_string()},
+        ];
+        if self.hidden_power_type.is_some() || self.pokeball.is_some() || pokemon.gigantamax || self.dynamax_level != 10 || pokemon.tera_type.is_some() {
+            let extras = [
+                optional(self.pokeball.as_deref().map(to_id)),
+                optional(self.hidden_power_type.map(|type_| format!("{type_:?}"))),
+                if pokemon.gigantamax {"G".to_string()} else {String::new()},
+                if self.dynamax_level == 10 {String::new()} else {self.dynamax_level.to_string()},
+                optional(pokemon.tera_type.map(|type_| format!("{type_:?}"))),
+            ];
+            fields[PACKED_FIELDS - 1] = [fields[PACKED_FIELDS - 1].clone()].into_iter().chain(extras).collect::<Vec<_>>().join(",");
+        }
+        fields.join("|")
+    }
+}
+
+/// Writes a team in Showdown's packed format.
+pub fn pack_team(team: &[TeamMember]) -> String {
+    team.iter().map(TeamMember::pack).collect::<Vec<_>>().join("]")
+}
+
+#[cfg(test)]
+#[cfg(feature = "real_data")]
+mod tests {
+    use crate::{dex::Dex, names::abilities::Ability, paste::PasteErrorReason, species::Stat, types::Type};
+
+    use super::pack_team;
+
+    const PACKED: &str = "Glimmy|glimmora|focussash|toxicdebris|mortalspin,powergem,spikes,stealthrock|Timid|,,,252,4,252|F|,0,,,,|||,,,,,Grass]\
+        Great Tusk||boosterenergy|protosynthesis|headlongrush,closecombat,knockoff,rapidspin|Jolly|,252,4,,,252||,,,,,|S|50|";
+
+    #[test]
+    fn packed_tests() {
+        let dex = Dex::default();
+        let team = dex.unpack_team(PACKED).unwrap();
+        assert_eq!(team.len(), 2);
+        let glimmora = &team[0];
+        assert_eq!(glimmora.nickname.as_deref(), Some("Glimmy"));
+        assert_eq!(glimmora.pokemon.ability, Ability::ToxicDebris);
+        assert_eq!(glimmora.pokemon.ivs.get(Stat::Attack), 0);
+        assert_eq!(glimmora.pokemon.evs.get(Stat::Speed), 252);
+        assert_eq!(glimmora.pokemon.tera_type, Some(Type::Grass));
+        assert_eq!(team[1].nickname, None);
+        assert_eq!(team[1].pokemon.level, 50);
+        assert!(team[1].pokemon.shiny);
+
+        // Every IV at 31 packs to nothing
+        assert_eq!(pack_team(&team), PACKED.replace("|,,,,,|", "||"));
+        let round_trip = dex.unpack_team(&pack_team(&team)).unwrap();
+        assert_eq!(pack_team(&round_trip), pack_team(&team));
+
+        // The same team through the paste format
+        let pasted = dex.import_team(&crate::paste::export_team(&team)).unwrap();
+        assert_eq!(pack_team(&pasted), pack_team(&team));
+
+        // Abilities can be given by their slot
+        let hidden = dex.unpack_pokemon("Garchomp|||H||||||||", 1).unwrap();
+        assert_eq!(hidden.pokemon.ability, Ability::RoughSkin);
+        let special = dex.unpack_pokemon("Greninja|||S||||||||", 1).unwrap();
+        assert_eq!(special.pokemon.ability, Ability::BattleBond);
+        assert!(dex.unpack_pokemon("Zygarde|||1||||||||", 1).is_err());
+
+        // The client leaves out the ability when it's in the first slot
+        let exported = dex.unpack_pokemon("Garchomp||choicescarf||earthquake,outrage,stoneedge,firefang|Jolly|,252,,,4,252|||||", 1).unwrap();
+        assert_eq!(exported.pokemon.ability, Ability::SandVeil);
+        assert_eq!(exported.pokemon.moves().count(), 4);
+        // ...and puts the ball before the Hidden Power type in the extras
+        let exported = "Pikachu||lightball||thunderbolt,voltswitch,hiddenpower,surf|Timid|,,,252,4,252||,30,,30,,|||,cherishball,Ice,,,Electric";
+        let pikachu = dex.unpack_pokemon(exported, 1).unwrap();
+        assert_eq!(pikachu.pokeball.as_deref(), Some("cherishball"));
+        assert_eq!(pikachu.hidden_power_type, Some(Type::Ice));
+        assert_eq!(pikachu.pokemon.tera_type, Some(Type::Electric));
+        assert_eq!(pikachu.pack(), exported.replace("||thunderbolt", "|static|thunderbolt"));
+
+        let error = dex.unpack_team(&PACKED.replace("knockoff", "knockof")).err().unwrap();
+        assert_eq!((error.line, error.token.as_str()), (2, "knockof"));
+        assert!(matches!(error.reason, PasteErrorReason::NotFound(_)));
+        assert_eq!(dex.unpack_pokemon("Garchomp|||", 1).err().unwrap().reason, PasteErrorReason::MissingFields);
+    }
+}
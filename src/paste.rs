@@ -0,0 +1,327 @@
+use std::fmt::Display;
+
+use serde::{de::{value::Error as ValueError, IntoDeserializer}, Deserialize};
+
+use crate::{dex::{Dex, DexError}, learnsets::Gender, names::{abilities::Ability, IdentifierName}, pokemon::{Pokemon, MAX_MOVES}, species::{Stat, StatDistribution}, types::Type};
+
+pub const MAX_TEAM_SIZE: usize = 6;
+
+/// The order and names of stats in EV and IV lines.
+pub(crate) const STAT_NAMES: [(Stat, &str); 6] = [
+    (Stat::HP, "HP"), (Stat::Attack, "Atk"), (Stat::Defence, "Def"),
+    (Stat::SpecialAttack, "SpA"), (Stat::SpecialDefence, "SpD"), (Stat::Speed, "Spe")
+];
+
+/// A pokemon on a team, with everything a Showdown paste can describe that [Pokemon] doesn't.
+#[derive(Clone)]
+pub struct TeamMember<'a> {
+    pub nickname: Option<String>,
+    pub pokemon: Pokemon<'a>,
+    pub pokeball: Option<String>,
+    /// The Hidden Power type chosen in formats where it doesn't depend on IVs.
+    pub hidden_power_type: Option<Type>,
+    pub dynamax_level: u8,
+}
+impl<'a> TeamMember<'a> {
+    pub fn new(pokemon: Pokemon<'a>) -> Self {
+        Self { nickname: None, pokemon, pokeball: None, hidden_power_type: None, dynamax_level: 10 }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PasteErrorReason {
+    NotFound(DexError),
+    InvalidNumber,
+    UnknownStat,
+    UnknownType,
+    UnknownLine,
+    TooManyMoves,
+    TooManyPokemon,
+    UnknownGender,
+    /// A packed pokemon with fewer fields than it needs.
+    MissingFields,
+}
+
+/// An error in a Showdown paste, with the line it is on (from 1) and the text that caused it.\
+/// For packed teams, `line` is the position of the pokemon in the team (from 1).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PasteError {
+    pub line: usize,
+    pub token: String,
+    pub reason: PasteErrorReason,
+}
+impl Display for PasteError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let reason = match &self.reason {
+            PasteErrorReason::NotFound(_) => "not found",
+            PasteErrorReason::InvalidNumber => "is not a valid number",
+            PasteErrorReason::UnknownStat => "is not a stat",
+            PasteErrorReason::UnknownType => "is not a type",
+            PasteErrorReason::UnknownLine => "is not a recognised line",
+            PasteErrorReason::TooManyMoves => "is more than 4 moves",
+            PasteErrorReason::TooManyPokemon => "is more than 6 pokemon",
+            PasteErrorReason::UnknownGender => "is not a gender",
+            PasteErrorReason::MissingFields => "is missing fields",
+        };
+        write!(f, "Line {}: '{}' {reason}", self.line, self.token)
+    }
+}
+
+/// Showdown's ID for a name, which keeps only letters and numbers. See "Farfetch’d".
+pub fn to_id(name: &str) -> String {
+    name.chars().filter(char::is_ascii_alphanumeric).map(|ch| ch.to_ascii_lowercase()).collect()
+}
+
+pub fn parse_type(name: &str) -> Option<Type> {
+    let mut chars = name.trim().chars();
+    let name = chars.next().map(|first| first.to_ascii_uppercase().to_string() + &chars.as_str().to_ascii_lowercase())?;
+    let deserializer: serde::de::value::StringDeserializer<ValueError> = name.into_deserializer();
+    Type::deserialize(deserializer).ok().filter(|type_| *type_ != Type::Unknown)
+}
+
+pub(crate) fn gender_letter(gender: Gender) -> &'static str {
+    match gender {
+        Gender::Male => "M",
+        Gender::Female => "F",
+        Gender::Unknown => "N",
+    }
+}
+
+impl Dex {
+    /// Reads a single pokemon in Showdown's paste format. `first_line` is the line number of its first line, for errors.
+    pub fn import_pokemon(&self, text: &str, first_line: usize) -> Result<TeamMember<'_>, PasteError> {
+        let mut lines = text.lines().enumerate()
+            .map(|(i, line)| (i + first_line, line.trim()))
+            .filter(|(_, line)| !line.is_empty());
+        let Some((line, header)) = lines.next() else {
+            return Err(PasteError { line: first_line, token: String::new(), reason: PasteErrorReason::UnknownLine })
+        };
+        let not_found = |token: &str, dex_error: DexError| PasteError { line, token: token.to_string(), reason: PasteErrorReason::NotFound(dex_error) };
+
+        // Nickname (Species) (M) @ Item
+        let (name, item) = match header.rsplit_once(" @ ") {
+            Some((name, item)) => (name.trim(), Some(item.trim())),
+            None => (header, None),
+        };
+        let (name, gender) = match name.strip_suffix(" (M)").map(|name| (name, Gender::Male))
+            .or_else(|| name.strip_suffix(" (F)").map(|name| (name, Gender::Female))) {
+            Some((name, gender)) => (name.trim(), Some(gender)),
+            None => (name, None),
+        };
+        let (nickname, species) = match name.strip_suffix(')').and_then(|name| name.rsplit_once(" (")) {
+            Some((nickname, species)) => (Some(nickname.trim().to_string()), species.trim()),
+            None => (None, name),
+        };
+        let mut pokemon = self.pokemon(to_id(species)).map_err(|dex_error| not_found(species, dex_error))?.level(100);
+        if let Some(item) = item {
+            pokemon = pokemon.item(self.item(to_id(item)).map_err(|dex_error| not_found(item, dex_error))?);
+        }
+        pokemon.gender = gender;
+        let mut member = TeamMember { nickname, ..TeamMember::new(pokemon) };
+
+        for (line, text) in lines {
+            let error = |token: &str, reason: PasteErrorReason| PasteError { line, token: token.to_string(), reason };
+            let number = |token: &str| token.trim().parse::<u8>().map_err(|_| error(token, PasteErrorReason::InvalidNumber));
+            let yes = |token: &str| token.trim().eq_ignore_ascii_case("yes");
+
+            if let Some(move_) = text.strip_prefix('-') {
+                let move_ = move_.trim();
+                if member.pokemon.moves().count() == MAX_MOVES {
+                    return Err(error(move_, PasteErrorReason::TooManyMoves))
+                }
+                let move_ = self.move_(to_id(move_)).map_err(|dex_error| error(move_, PasteErrorReason::NotFound(dex_error)))?;
+                member.pokemon = member.pokemon.move_(move_);
+            } else if let Some(nature) = text.strip_suffix(" Nature") {
+                let nature = self.nature(to_id(nature)).map_err(|dex_error| error(nature, PasteErrorReason::NotFound(dex_error)))?;
+                member.pokemon = member.pokemon.nature(nature);
+            } else if let Some((key, value)) = text.split_once(':') {
+                let value = value.trim();
+                match key.trim() {
+                    "Ability" => {
+                        let ability = Ability::try_from(IdentifierName::new(to_id(value)))
+                            .map_err(|dex_error| error(value, PasteErrorReason::NotFound(dex_error)))?;
+                        member.pokemon = member.pokemon.ability(ability);
+                    },
+                    "Level" => member.pokemon = member.pokemon.level(number(value)?),
+                    "Happiness" => member.pokemon = member.pokemon.happiness(number(value)?),
+                    "Shiny" => member.pokemon.shiny = yes(value),
+                    "Tera Type" => member.pokemon.tera_type = Some(parse_type(value).ok_or_else(|| error(value, PasteErrorReason::UnknownType))?),
+                    "EVs" => member.pokemon.evs = parse_stats(value, StatDistribution::default(), &error)?,
+                    "IVs" => member.pokemon.ivs = parse_stats(value, member.pokemon.ivs, &error)?,
+                    "Pokeball" => member.pokeball = Some(value.to_string()),
+                    "Hidden Power" => member.hidden_power_type = Some(parse_type(value).ok_or_else(|| error(value, PasteErrorReason::UnknownType))?),
+                    "Dynamax Level" => member.dynamax_level = number(value)?,
+                    "Gigantamax" => member.pokemon.gigantamax = yes(value),
+                    _ => return Err(error(text, PasteErrorReason::UnknownLine)),
+                }
+            } else {
+                return Err(error(text, PasteErrorReason::UnknownLine))
+            }
+        }
+        Ok(member)
+    }
+
+    /// Reads a team in Showdown's paste format, where each pokemon is separated by a blank line.
+    pub fn import_team(&self, text: &str) -> Result<Vec<TeamMember<'_>>, PasteError> {
+        let mut team = vec![];
+        let mut start = 0;
+        let lines = text.lines().collect::<Vec<_>>();
+        for (i, line) in lines.iter().enumerate().chain([(lines.len(), &"")]) {
+            if !line.trim().is_empty() {
+                continue
+            }
+            let set = lines[start..i].join("\n");
+            if !set.trim().is_empty() {
+                if team.len() == MAX_TEAM_SIZE {
+                    return Err(PasteError { line: start + 1, token: lines[start..i].iter().find(|line| !line.trim().is_empty()).unwrap_or(&"").to_string(), reason: PasteErrorReason::TooManyPokemon })
+                }
+                team.push(self.import_pokemon(&set, start + 1)?);
+            }
+            start = i + 1;
+        }
+        Ok(team)
+    }
+}
+
+/// Reads an EV or IV line like "252 Atk / 4 SpD / 252 Spe". Stats that aren't listed keep their value in `stats`.
+fn parse_stats(text: &str, mut stats: StatDistribution, error: &impl Fn(&str, PasteErrorReason) -> PasteError) -> Result<StatDistribution, PasteError> {
+    for part in text.split('/') {
+        let (value, name) = part.trim().split_once(' ').ok_or_else(|| error(part.trim(), PasteErrorReason::UnknownStat))?;
+        let value = value.parse::<u8>().map_err(|_| error(value, PasteErrorReason::InvalidNumber))?;
+        let stat = STAT_NAMES.iter().find(|(_, stat_name)| stat_name.eq_ignore_ascii_case(name.trim()))
+            .ok_or_else(|| error(name.trim(), PasteErrorReason::UnknownStat))?.0;
+        *stats.get_mut(stat) = value;
+    }
+    Ok(stats)
+}
+
+/// Writes stats that differ from `default` as an EV or IV line. `None` if every stat is the default.
+fn export_stats(stats: &StatDistribution, default: u8) -> Option<String> {
+    let parts = STAT_NAMES.iter()
+        .filter(|(stat, _)| stats.get(*stat) != default)
+        .map(|(stat, name)| format!("{} {name}", stats.get(*stat)))
+        .collect::<Vec<_>>();
+    (!parts.is_empty()).then(|| parts.join(" / "))
+}
+
+impl Display for TeamMember<'_> {
+    /// Writes this pokemon in Showdown's paste format.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let pokemon = &self.pokemon;
+        match &self.nickname {
+            Some(nickname) => write!(f, "{nickname} ({})", pokemon.species.name)?,
+            None => write!(f, "{}", pokemon.species.name)?,
+        }
+        if let Some(gender) = pokemon.gender.filter(|gender| *gender != Gender::Unknown) {
+            write!(f, " ({})", gender_letter(gender))?;
+        }
+        if let Some(item) = pokemon.item {
+            write!(f, " @ {}", item.name)?;
+        }
+        writeln!(f)?;
+        if pokemon.ability != Ability::NoAbility {
+            writeln!(f, "Ability: {}", pokemon.ability.name())?;
+        }
+        if pokemon.level != 100 {
+            writeln!(f, "Level: {}", pokemon.level)?;
+        }
+        if pokemon.shiny {
+            writeln!(f, "Shiny: Yes")?;
+        }
+        if pokemon.happiness != 255 {
+            writeln!(f, "Happiness: {}", pokemon.happiness)?;
+        }
+        if let Some(pokeball) = &self.pokeball {
+            writeln!(f, "Pokeball: {pokeball}")?;
+        }
+        if let Some(hidden_power_type) = self.hidden_power_type {
+            writeln!(f, "Hidden Power: {hidden_power_type:?}")?;
+        }
+        if self.dynamax_level != 10 {
+            writeln!(f, "Dynamax Level: {}", self.dynamax_level)?;
+        }
+        if pokemon.gigantamax {
+            writeln!(f, "Gigantamax: Yes")?;
+        }
+        if let Some(tera_type) = pokemon.tera_type {
+            writeln!(f, "Tera Type: {tera_type:?}")?;
+        }
+        if let Some(evs) = export_stats(&pokemon.evs, 0) {
+            writeln!(f, "EVs: {evs}")?;
+        }
+        if let Some(nature) = pokemon.nature {
+            writeln!(f, "{} Nature", nature.name)?;
+        }
+        if let Some(ivs) = export_stats(&pokemon.ivs, 31) {
+            writeln!(f, "IVs: {ivs}")?;
+        }
+        for slot in pokemon.moves() {
+            writeln!(f, "- {}", slot.move_.name)?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes a team in Showdown's paste format.
+pub fn export_team(team: &[TeamMember]) -> String {
+    team.iter().map(|member| member.to_string()).collect::<Vec<_>>().join("\n")
+}
+
+#[cfg(test)]
+#[cfg(feature = "real_data")]
+mod tests {
+    use crate::{dex::Dex, names::abilities::Ability, species::Stat, types::Type};
+
+    use super::{export_team, PasteErrorReason};
+
+    const TEAM: &str = "Glimmy (Glimmora) (F) @ Focus Sash
+Ability: Toxic Debris
+Level: 50
+Tera Type: Grass
+EVs: 252 SpA / 4 SpD / 252 Spe
+Timid Nature
+IVs: 0 Atk
+- Mortal Spin
+- Power Gem
+- Spikes
+- Stealth Rock
+
+Great Tusk @ Booster Energy
+Ability: Protosynthesis
+Shiny: Yes
+EVs: 252 Atk / 4 Def / 252 Spe
+Jolly Nature
+- Headlong Rush
+- Close Combat
+- Knock Off
+- Rapid Spin
+";
+
+    #[test]
+    fn paste_tests() {
+        let dex = Dex::default();
+        let team = dex.import_team(TEAM).unwrap();
+        assert_eq!(team.len(), 2);
+        let glimmora = &team[0];
+        assert_eq!(glimmora.nickname.as_deref(), Some("Glimmy"));
+        assert_eq!(glimmora.pokemon.ability, Ability::ToxicDebris);
+        assert_eq!(glimmora.pokemon.level, 50);
+        assert_eq!(glimmora.pokemon.ivs.get(Stat::Attack), 0);
+        assert_eq!(glimmora.pokemon.evs.get(Stat::SpecialAttack), 252);
+        assert_eq!(glimmora.pokemon.tera_type, Some(Type::Grass));
+        assert_eq!(glimmora.pokemon.moves().count(), 4);
+        assert!(team[1].pokemon.shiny);
+
+        assert_eq!(export_team(&team), TEAM);
+        let ursaluna = dex.import_team("Ursaluna-Bloodmoon\nAbility: Mind's Eye\n- Blood Moon\n").unwrap();
+        assert_eq!(ursaluna[0].pokemon.ability, Ability::MindsEye);
+        assert_eq!(export_team(&ursaluna), "Ursaluna-Bloodmoon\nAbility: Mind's Eye\n- Blood Moon\n");
+        assert_eq!(Ability::RKSSystem.name(), "RKS System");
+
+        let error = dex.import_team(&TEAM.replace("Knock Off", "Knock Of")).err().unwrap();
+        assert_eq!((error.line, error.token.as_str()), (20, "Knock Of"));
+        assert!(matches!(error.reason, PasteErrorReason::NotFound(_)));
+        let error = dex.import_team(&TEAM.replace("252 Atk", "252 Atack")).err().unwrap();
+        assert_eq!((error.line, error.token.as_str(), error.reason), (16, "Atack", PasteErrorReason::UnknownStat));
+    }
+}
@@ -1,20 +1,107 @@
-use crate::{natures::NatureData, species::{Species, Stat, StatDistribution}};
+use std::fmt::Display;
+
+use crate::{enum_set::EnumSet, field::Field, generation::Generation, hidden_power::{hidden_power_power, hidden_power_type, HiddenPowerMismatch}, items::ItemData, learnsets::Gender, moves::{BoostsList, MoveData, PseudoWeather, Status, VolatileStatus}, natures::NatureData, species::{Species, Stat, StatDistribution}, types::Type};
 use crate::names::abilities::Ability;
 
+pub const MAX_MOVES: usize = 4;
+
+/// A move a pokemon knows, with its PP.
+#[derive(Clone, Copy)]
+pub struct MoveSlot<'a> {
+    pub move_: &'a MoveData,
+    /// From 0 to 3. Moves like Sketch can't have PP Ups.
+    pub pp_ups: u8,
+    pub pp: u8,
+}
+impl<'a> MoveSlot<'a> {
+    /// A move with full PP and 3 PP Ups, which is what Showdown gives every move.
+    /// Set [MoveSlot::pp_ups] or use [Pokemon::pp_ups] for fewer.
+    pub fn new(move_: &'a MoveData, gen: Generation) -> Self {
+        let mut slot = Self { move_, pp_ups: 3, pp: 0 };
+        slot.pp = slot.max_pp(gen);
+        slot
+    }
+    /// The move's PP after PP Ups, which each add a fifth of its base PP. Capped at 61 before Gen 3.
+    pub fn max_pp(&self, gen: Generation) -> u8 {
+        let pp_ups = if self.move_.no_pp_boosts {0} else {self.pp_ups.min(3)} as u16;
+        let max_pp = (self.move_.pp as u16 * (5 + pp_ups) / 5) as u8;
+        if gen < Generation::Three {max_pp.min(61)} else {max_pp}
+    }
+}
+
+/// A gender that a pokemon's species can't be. See [Pokemon::check_gender].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GenderMismatch {
+    pub claimed: Gender,
+    /// The genders the species can be.
+    pub possible: &'static [Gender],
+}
+impl Display for GenderMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Gender should be one of {:?}, but is {:?}", self.possible, self.claimed)
+    }
+}
+
 #[derive(Clone, Copy)]
 pub struct Pokemon<'a> {
     pub species: &'a Species,
+    /// The generation of the [Dex](crate::dex::Dex) this pokemon is from.
+    pub gen: Generation,
     pub level: u8,
     pub ability: Ability,
     pub nature: Option<&'a NatureData>,
+    pub item: Option<&'a ItemData>,
     pub evs: StatDistribution,
-    pub ivs: StatDistribution
+    pub ivs: StatDistribution,
+    /// Used instead of EVs in Gen 1-2.
+    pub stat_exp: StatDistribution<u16>,
+    /// Whether this pokemon is from Let's Go Pikachu/Eevee, which has its own stat formula.
+    pub lets_go: bool,
+    /// Awakening Values, used instead of EVs in Let's Go.
+    pub avs: StatDistribution,
+    pub happiness: u8,
+    pub boosts: BoostsList,
+    pub status: Option<Status>,
+    /// The number of turns this pokemon has already taken Toxic damage for.
+    pub toxic_turns: u8,
+    /// The number of times this pokemon has used Stockpile. See Spit Up.
+    pub stockpile: u8,
+    pub volatiles: EnumSet<VolatileStatus>,
+    pub moves: [Option<MoveSlot<'a>>; MAX_MOVES],
+    /// `None` if no gender was chosen.
+    pub gender: Option<Gender>,
+    pub shiny: bool,
+    pub tera_type: Option<Type>,
+    /// Whether this pokemon has the Gigantamax factor.
+    pub gigantamax: bool,
+    /// `None` is full HP.
+    pub current_hp: Option<u16>,
 }
 impl<'a> Pokemon<'a> {
-    pub fn new(species: &'a Species) -> Self {
-        Self { species, level: 50, ability: Ability::NoAbility, nature:None, evs: StatDistribution::default(), ivs:[31, 31, 31, 31, 31, 31].into() }
+    /// A level 50 pokemon with the species' first ability, from Gen 3 on.
+    pub fn new(species: &'a Species, gen: Generation) -> Self {
+        let ability = match gen {
+            Generation::One | Generation::Two => Ability::NoAbility,
+            _ => species.abilities.normal.first().copied().unwrap_or(Ability::NoAbility),
+        };
+        Self {
+            species, gen, level: 50, ability, nature:None, item: None, evs: StatDistribution::default(), ivs:[31, 31, 31, 31, 31, 31].into(), stat_exp: StatDistribution::default(), lets_go: false, avs: StatDistribution::default(), happiness: 255, boosts: BoostsList::default(), status: None, toxic_turns: 0, stockpile: 0, volatiles: EnumSet::new(),
+            moves: [None; MAX_MOVES], gender: None, shiny: false, tera_type: None, gigantamax: false, current_hp: None
+        }
     }
+    /// This pokemon's stat, using the formula for its generation.
     pub fn stat(&self, stat: Stat) -> u16 {
+        // Shedinja always has 1 HP
+        if let (Stat::HP, Some(max_hp)) = (stat, self.species.max_hp) {
+            return max_hp as u16
+        }
+        match self.gen {
+            _ if self.lets_go => self.lets_go_stat(stat),
+            Generation::One | Generation::Two => self.game_boy_stat(stat),
+            _ => self.modern_stat(stat),
+        }
+    }
+    fn modern_stat(&self, stat: Stat) -> u16 {
         let base_stat = self.species.base_stats.get(stat) as f32;
         let nature_multiplier = self.nature.as_ref().map_or(1.0, |nature| nature.multiplier(stat));
         let ev = self.evs.get(stat) as f32;
@@ -25,6 +112,134 @@ impl<'a> Pokemon<'a> {
             _ => (((2.0*base_stat + iv + ev/4.0)*level/100.0 + 5.0).floor() * nature_multiplier).floor() as u16,
         }
     }
+    /// Gen 1-2 stats use DVs and Stat Experience, and have no natures.\
+    /// Special defence shares the special DV and Stat Experience with special attack. In Gen 1 they are the same Special stat.
+    fn game_boy_stat(&self, stat: Stat) -> u16 {
+        let special_stat = match stat {
+            Stat::SpecialDefence => Stat::SpecialAttack,
+            _ => stat
+        };
+        let base_stat = match self.gen {
+            Generation::One => self.species.base_stats.get(special_stat),
+            _ => self.species.base_stats.get(stat)
+        } as u32;
+        let dv = self.stat_dv(special_stat) as u32;
+        let stat_exp = (self.stat_exp.get(special_stat) as f32).sqrt().ceil().min(255.0) as u32 / 4;
+        let level = self.level as u32;
+        let value = ((base_stat + dv) * 2 + stat_exp) * level / 100;
+        match stat {
+            Stat::HP => (value + level + 10) as u16,
+            _ => (value + 5) as u16,
+        }
+    }
+    /// Let's Go stats add AVs at the end instead of using EVs, and friendship raises stats other than HP by up to 10%.\
+    /// Partner Pikachu and Eevee get their bonus from the base stats of their Starter formes.
+    fn lets_go_stat(&self, stat: Stat) -> u16 {
+        let base_stat = self.species.base_stats.get(stat) as u32;
+        let iv = self.ivs.get(stat) as u32;
+        let av = self.avs.get(stat) as u32;
+        let level = self.level as u32;
+        let value = (2 * base_stat + iv) * level / 100;
+        match stat {
+            Stat::HP => (value + level + 10 + av) as u16,
+            _ => {
+                let nature_multiplier = self.nature.as_ref().map_or(1.0, |nature| nature.multiplier(stat));
+                let value = ((value + 5) as f32 * nature_multiplier).floor() as u32;
+                let friendship_bonus = 10 * self.happiness as u32 / 255;
+                (value * (100 + friendship_bonus) / 100 + av) as u16
+            }
+        }
+    }
+    /// The DV used for a stat in Gen 1-2. The HP DV is made from the lowest bit of each other DV.
+    pub fn stat_dv(&self, stat: Stat) -> u8 {
+        match stat {
+            Stat::HP => [Stat::Attack, Stat::Defence, Stat::Speed, Stat::SpecialAttack].into_iter()
+                .fold(0, |hp_dv, stat| (hp_dv << 1) | (self.stat_dv(stat) & 1)),
+            Stat::SpecialDefence => self.stat_dv(Stat::SpecialAttack),
+            _ => iv_to_dv(self.ivs.get(stat))
+        }
+    }
+    /// In Gen 2, gender is decided by the attack DV.
+    pub fn dv_gender(&self) -> Gender {
+        if let Some(gender) = self.species.gender {
+            return gender
+        }
+        let female_ratio = self.species.gender_ratio.map_or(0.5, |ratio| ratio.f);
+        if (self.stat_dv(Stat::Attack) as f32) < 16.0 * female_ratio {
+            Gender::Female
+        } else {
+            Gender::Male
+        }
+    }
+    /// In Gen 2, a pokemon is shiny if its defence, speed and special DVs are 10 and its attack DV is 2, 3, 6, 7, 10, 11, 14 or 15.
+    pub fn dv_shiny(&self) -> bool {
+        [Stat::Defence, Stat::Speed, Stat::SpecialAttack].into_iter().all(|stat| self.stat_dv(stat) == 10)
+            && self.stat_dv(Stat::Attack) & 2 == 2
+    }
+    /// The held item, unless it is suppressed by Klutz or Magic Room.
+    pub fn active_item(&self, field: &Field) -> Option<&'a ItemData> {
+        let item = self.item?;
+        let suppressed = field.has_pseudo_weather(PseudoWeather::MagicRoom) || (self.ability == Ability::Klutz && !item.ignore_klutz);
+        (!suppressed).then_some(item)
+    }
+    /// This pokemon's HP, which Dynamax doubles (at Dynamax Level 10).
+    pub fn max_hp(&self) -> u16 {
+        let hp = self.stat(Stat::HP);
+        if self.volatiles.contains(VolatileStatus::Dynamax) {hp * 2} else {hp}
+    }
+    /// The HP this pokemon has left.
+    pub fn hp(&self) -> u16 {
+        let max_hp = self.max_hp();
+        self.current_hp.map_or(max_hp, |hp| hp.min(max_hp))
+    }
+    /// The moves this pokemon knows, skipping empty slots.
+    pub fn moves(&self) -> impl Iterator<Item = &MoveSlot<'a>> {
+        self.moves.iter().flatten()
+    }
+    /// The genders this pokemon's species can be.
+    pub fn possible_genders(&self) -> &'static [Gender] {
+        match (self.species.gender, self.species.gender_ratio) {
+            (Some(Gender::Male), _) => &[Gender::Male],
+            (Some(Gender::Female), _) => &[Gender::Female],
+            (Some(Gender::Unknown), _) => &[Gender::Unknown],
+            (None, Some(ratio)) if ratio.f == 0.0 => &[Gender::Male],
+            (None, Some(ratio)) if ratio.m == 0.0 => &[Gender::Female],
+            (None, _) => &[Gender::Male, Gender::Female],
+        }
+    }
+    /// Checks that this pokemon's gender, if it has one, is one its species can be. See Gallade.
+    pub fn check_gender(&self) -> Result<(), GenderMismatch> {
+        let possible = self.possible_genders();
+        match self.gender {
+            Some(claimed) if !possible.contains(&claimed) => Err(GenderMismatch { claimed, possible }),
+            _ => Ok(())
+        }
+    }
+    /// Whether this pokemon is affected by terrain and ground moves.
+    pub fn is_grounded(&self, field: &Field) -> bool {
+        let item = self.active_item(field).map(|item| item.id());
+        let grounding_volatile = [VolatileStatus::Smackdown, VolatileStatus::Ingrain].iter().any(|volatile| self.volatiles.contains(*volatile));
+        if field.has_pseudo_weather(PseudoWeather::Gravity) || item.as_deref() == Some("ironball") || grounding_volatile {
+            return true
+        }
+        !(self.species.types.contains(&Type::Flying) || self.ability == Ability::Levitate || item.as_deref() == Some("airballoon")
+            || self.volatiles.contains(VolatileStatus::Magnetrise) || self.volatiles.contains(VolatileStatus::Telekinesis))
+    }
+    pub fn hidden_power_type(&self) -> Type {
+        hidden_power_type(&self.ivs, self.gen)
+    }
+    pub fn hidden_power_power(&self) -> u8 {
+        hidden_power_power(&self.ivs, self.gen)
+    }
+    /// Checks that a Hidden Power type claimed for this pokemon is the one its IVs give. See Hidden Power Fire.
+    pub fn check_hidden_power_type(&self, claimed: Type) -> Result<(), HiddenPowerMismatch> {
+        let actual = self.hidden_power_type();
+        if actual == claimed {
+            Ok(())
+        } else {
+            Err(HiddenPowerMismatch { claimed, actual })
+        }
+    }
 }
 
 impl<'a> Pokemon<'a> {
@@ -44,12 +259,221 @@ impl<'a> Pokemon<'a> {
         *self.evs.get_mut(stat) = ev;
         self
     }
+    /// Sets the IVs from Gen 1-2 DVs. The HP DV is ignored as it comes from the other DVs.
+    pub fn dvs(mut self, dvs:impl Into<StatDistribution>) -> Self {
+        let dvs = dvs.into();
+        self.ivs = [dvs.hp, dvs.attack, dvs.defence, dvs.special_attack, dvs.special_defence, dvs.speed].map(dv_to_iv).into();
+        self
+    }
+    pub fn dv(mut self, stat:Stat, dv:u8) -> Self {
+        *self.ivs.get_mut(stat) = dv_to_iv(dv);
+        self
+    }
+    pub fn stat_exps(mut self, stat_exp:impl Into<StatDistribution<u16>>) -> Self {
+        self.stat_exp = stat_exp.into();
+        self
+    }
+    pub fn stat_exp(mut self, stat:Stat, stat_exp:u16) -> Self {
+        *self.stat_exp.get_mut(stat) = stat_exp;
+        self
+    }
+    /// Uses Let's Go's stat formula. Let's Go has no abilities, so this also removes the ability.
+    pub fn lets_go(mut self, lets_go:bool) -> Self {
+        self.lets_go = lets_go;
+        if lets_go {
+            self.ability = Ability::NoAbility;
+        }
+        self
+    }
+    pub fn avs(mut self, avs:impl Into<StatDistribution>) -> Self {
+        self.avs = avs.into();
+        self
+    }
+    pub fn av(mut self, stat:Stat, av:u8) -> Self {
+        *self.avs.get_mut(stat) = av;
+        self
+    }
+    pub fn happiness(mut self, happiness:u8) -> Self {
+        self.happiness = happiness;
+        self
+    }
     pub fn level(mut self, level:u8) -> Self {
         self.level = level;
         self
     }
+    pub fn ability(mut self, ability: Ability) -> Self {
+        self.ability = ability;
+        self
+    }
     pub fn nature(mut self, nature: &'a NatureData) -> Self {
         self.nature = Some(nature);
         self
     }
-}
\ No newline at end of file
+    pub fn item(mut self, item: &'a ItemData) -> Self {
+        self.item = Some(item);
+        self
+    }
+    pub fn status(mut self, status: Status) -> Self {
+        self.status = Some(status);
+        self
+    }
+    pub fn toxic_turns(mut self, toxic_turns: u8) -> Self {
+        self.toxic_turns = toxic_turns;
+        self
+    }
+    pub fn stockpile(mut self, stockpile: u8) -> Self {
+        self.stockpile = stockpile.min(3);
+        if self.stockpile > 0 {
+            self.volatiles.insert(VolatileStatus::Stockpile);
+        } else {
+            self.volatiles.remove(VolatileStatus::Stockpile);
+        }
+        self
+    }
+    pub fn volatile(mut self, volatile: VolatileStatus) -> Self {
+        self.volatiles.insert(volatile);
+        self
+    }
+    pub fn boosts(mut self, boosts: BoostsList) -> Self {
+        self.boosts = boosts;
+        self
+    }
+    pub fn boost(mut self, stat:Stat, boost:i8) -> Self {
+        *self.boosts.get_mut(stat) = boost;
+        self
+    }
+    /// Teaches a move in the first empty slot, with full PP Ups. Does nothing if every slot is full.
+    pub fn move_(mut self, move_: &'a MoveData) -> Self {
+        if let Some(slot) = self.moves.iter_mut().find(|slot| slot.is_none()) {
+            *slot = Some(MoveSlot::new(move_, self.gen));
+        }
+        self
+    }
+    /// Sets the PP Ups of the move in the given slot, and refills its PP.
+    pub fn pp_ups(mut self, slot: usize, pp_ups: u8) -> Self {
+        if let Some(Some(slot)) = self.moves.get_mut(slot) {
+            slot.pp_ups = pp_ups.min(3);
+            slot.pp = slot.max_pp(self.gen);
+        }
+        self
+    }
+    /// Sets the PP left for the move in the given slot.
+    pub fn pp(mut self, slot: usize, pp: u8) -> Self {
+        if let Some(Some(slot)) = self.moves.get_mut(slot) {
+            slot.pp = pp.min(slot.max_pp(self.gen));
+        }
+        self
+    }
+    pub fn gender(mut self, gender: Gender) -> Self {
+        self.gender = Some(gender);
+        self
+    }
+    pub fn shiny(mut self, shiny: bool) -> Self {
+        self.shiny = shiny;
+        self
+    }
+    pub fn tera_type(mut self, tera_type: Type) -> Self {
+        self.tera_type = Some(tera_type);
+        self
+    }
+    pub fn gigantamax(mut self, gigantamax: bool) -> Self {
+        self.gigantamax = gigantamax;
+        self
+    }
+    pub fn current_hp(mut self, hp: u16) -> Self {
+        self.current_hp = Some(hp);
+        self
+    }
+}
+
+/// Before Gen 3, DVs from 0-15 are used instead of IVs. They are stored as IVs, where every DV covers two IVs.
+pub fn iv_to_dv(iv: u8) -> u8 {
+    iv / 2
+}
+pub fn dv_to_iv(dv: u8) -> u8 {
+    dv * 2 + 1
+}
+
+/// Applies a stat stage to a stat other than accuracy or evasion. Stages are clamped to +-6.
+pub fn apply_boost(stat: u16, boost: i8) -> u16 {
+    let boost = boost.clamp(-6, 6) as i32;
+    let stat = stat as i32;
+    match boost {
+        0.. => (stat * (2 + boost) / 2) as u16,
+        _ => (stat * 2 / (2 - boost)) as u16,
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "real_data")]
+mod tests {
+    use crate::{dex::{Dex, DexError}, generation::Generation, learnsets::Gender, moves::NonStandardReason, names::abilities::Ability, species::Stat};
+
+    #[test]
+    fn game_boy_stat_tests() -> Result<(), DexError> {
+        let dex = Dex::generation_dex(Generation::One);
+        let tauros = dex.pokemon("tauros")?.level(100).stat_exps([u16::MAX; 6]);
+        assert_eq!([Stat::HP, Stat::Attack, Stat::Defence, Stat::SpecialAttack, Stat::SpecialDefence, Stat::Speed].map(|stat| tauros.stat(stat)), [353, 298, 288, 238, 238, 318]);
+
+        let tauros = tauros.dv(Stat::Attack, 14).dv(Stat::Speed, 12);
+        assert_eq!(tauros.stat_dv(Stat::HP), 5);
+        assert_eq!(tauros.stat(Stat::HP), 333);
+
+        let dex = Dex::generation_dex(Generation::Two);
+        let gyarados = dex.pokemon("gyarados")?.dvs([0, 10, 10, 10, 10, 10]);
+        assert!(gyarados.dv_shiny());
+        assert_eq!(gyarados.dv_gender(), Gender::Male);
+        assert_eq!(gyarados.dv(Stat::Attack, 5).dv_gender(), Gender::Female);
+        Ok(())
+    }
+
+    #[test]
+    fn lets_go_stat_tests() -> Result<(), DexError> {
+        let dex = Dex::lets_go_dex();
+        let pikachu = dex.pokemon("pikachustarter")?.avs([200; 6]);
+        assert_eq!(pikachu.stat(Stat::HP), 320);
+        assert_eq!(pikachu.stat(Stat::Speed), 354);
+        assert_eq!(pikachu.happiness(0).stat(Stat::Speed), 340);
+
+        assert!(dex.species("melmetal").is_ok());
+        assert!(dex.species("chikorita").is_err());
+        assert!(dex.move_("bouncybubble").is_ok());
+        assert!(dex.move_("payday").is_ok());
+        assert!(dex.move_("playrough").is_ok());
+        assert!(dex.move_("uturn").is_err());
+        assert!(dex.move_("stealthrock").is_err());
+        // Let's Go exclusives are still marked, but are available in a Let's Go dex
+        let zippy_zap = dex.move_("zippyzap")?.is_nonstandard;
+        assert_eq!(zippy_zap, Some(NonStandardReason::LGPE));
+        assert_eq!(dex.nonstandard_reason(zippy_zap), None);
+        assert_eq!(Dex::generation_dex(Generation::Seven).nonstandard_reason(zippy_zap), Some(NonStandardReason::LGPE));
+        Ok(())
+    }
+
+    #[test]
+    fn full_set_tests() -> Result<(), DexError> {
+        let dex = Dex::default();
+        let garchomp = dex.pokemon("garchomp")?;
+        assert_eq!(garchomp.ability, Ability::SandVeil);
+        let gen_2 = Dex::generation_dex(Generation::Two);
+        assert_eq!(gen_2.pokemon("snorlax")?.ability, Ability::NoAbility);
+        assert_eq!(Dex::lets_go_dex().pokemon("pikachustarter")?.ability, Ability::NoAbility);
+
+        let garchomp = garchomp.move_(dex.move_("earthquake")?).move_(dex.move_("sketch")?).pp_ups(1, 3).pp(0, 3);
+        let moves = garchomp.moves().collect::<Vec<_>>();
+        assert_eq!((moves[0].pp, moves[0].max_pp(garchomp.gen)), (3, 16));
+        assert_eq!(moves[1].pp, 1);
+        assert_eq!(garchomp.pp_ups(0, 0).moves[0].unwrap().pp, 10);
+        let growl = gen_2.pokemon("snorlax")?.move_(gen_2.move_("growl")?);
+        assert_eq!(growl.moves[0].unwrap().pp, 61);
+
+        assert!(garchomp.gender(Gender::Female).check_gender().is_ok());
+        assert!(dex.pokemon("gallade")?.gender(Gender::Female).check_gender().is_err());
+        assert!(dex.pokemon("magnezone")?.gender(Gender::Male).check_gender().is_err());
+
+        assert_eq!(garchomp.current_hp(500).hp(), garchomp.max_hp());
+        assert_eq!(garchomp.current_hp(20).hp(), 20);
+        assert_eq!(dex.pokemon("shedinja")?.level(100).max_hp(), 1);
+        Ok(())
+    }
+}
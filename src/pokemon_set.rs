@@ -0,0 +1,190 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{dex::{Dex, DexError}, learnsets::Gender, names::{abilities::Ability, IdentifierName}, paste::{to_id, TeamMember}, pokemon::Pokemon, species::StatDistribution, types::Type};
+
+/// A pokemon set that owns its data, so it can be stored and serialized, unlike [Pokemon].\
+/// Species, items, abilities, moves and natures are kept as Showdown IDs and looked up with [Dex::resolve_set].
+/// The JSON layout matches Showdown's own sets.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PokemonSet {
+    #[serde(rename = "name", default, skip_serializing_if = "Option::is_none")]
+    pub nickname: Option<String>,
+    pub species: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub item: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ability: Option<String>,
+    #[serde(default)]
+    pub moves: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nature: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gender: Option<Gender>,
+    #[serde(default)]
+    pub evs: StatDistribution,
+    #[serde(default = "perfect_ivs")]
+    pub ivs: StatDistribution,
+    /// Stat Experience, which takes the place of EVs in Gen 1 and 2.
+    #[serde(default)]
+    pub stat_exp: StatDistribution<u16>,
+    /// Awakening Values, which take the place of EVs in Let's Go.
+    #[serde(default)]
+    pub avs: StatDistribution,
+    #[serde(default = "max_level")]
+    pub level: u8,
+    #[serde(default)]
+    pub shiny: bool,
+    #[serde(default = "max_happiness")]
+    pub happiness: u8,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pokeball: Option<String>,
+    #[serde(rename = "hpType", default, skip_serializing_if = "Option::is_none")]
+    pub hidden_power_type: Option<Type>,
+    #[serde(default)]
+    pub gigantamax: bool,
+    #[serde(default = "max_dynamax_level")]
+    pub dynamax_level: u8,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tera_type: Option<Type>,
+}
+
+fn perfect_ivs() -> StatDistribution {
+    [31, 31, 31, 31, 31, 31].into()
+}
+fn max_level() -> u8 {
+    100
+}
+fn max_happiness() -> u8 {
+    255
+}
+fn max_dynamax_level() -> u8 {
+    10
+}
+
+impl PokemonSet {
+    /// A level 100 set of the given species, with nothing else set.
+    pub fn new(species: impl Into<String>) -> Self {
+        Self {
+            nickname: None, species: species.into(), item: None, ability: None, moves: vec![], nature: None, gender: None,
+            evs: StatDistribution::default(), ivs: perfect_ivs(), stat_exp: StatDistribution::default(), avs: StatDistribution::default(), level: max_level(), shiny: false, happiness: max_happiness(),
+            pokeball: None, hidden_power_type: None, gigantamax: false, dynamax_level: max_dynamax_level(), tera_type: None
+        }
+    }
+}
+
+impl From<&TeamMember<'_>> for PokemonSet {
+    fn from(member: &TeamMember<'_>) -> Self {
+        let pokemon = &member.pokemon;
+        Self {
+            nickname: member.nickname.clone(),
+            species: to_id(&pokemon.species.name),
+            item: pokemon.item.map(|item| item.id()),
+            ability: (pokemon.ability != Ability::NoAbility).then(|| to_id(&pokemon.ability.name())),
+            moves: pokemon.moves().map(|slot| to_id(&slot.move_.name)).collect(),
+            nature: pokemon.nature.map(|nature| to_id(&nature.name)),
+            gender: pokemon.gender,
+            evs: pokemon.evs,
+            ivs: pokemon.ivs,
+            stat_exp: pokemon.stat_exp,
+            avs: pokemon.avs,
+            level: pokemon.level,
+            shiny: pokemon.shiny,
+            happiness: pokemon.happiness,
+            pokeball: member.pokeball.clone(),
+            hidden_power_type: member.hidden_power_type,
+            gigantamax: pokemon.gigantamax,
+            dynamax_level: member.dynamax_level,
+            tera_type: pokemon.tera_type,
+        }
+    }
+}
+
+impl Dex {
+    /// Looks up everything in a set, using this Dex's generation.
+    pub fn resolve_set(&self, set: &PokemonSet) -> Result<TeamMember<'_>, DexError> {
+        let mut pokemon = self.pokemon(to_id(&set.species))?.level(set.level).happiness(set.happiness);
+        if let Some(item) = &set.item {
+            pokemon = pokemon.item(self.item(to_id(item))?);
+        }
+        if let Some(ability) = &set.ability {
+            pokemon = pokemon.ability(Ability::try_from(IdentifierName::new(to_id(ability)))?);
+        }
+        if let Some(nature) = &set.nature {
+            pokemon = pokemon.nature(self.nature(to_id(nature))?);
+        }
+        for move_ in set.moves.iter() {
+            pokemon = pokemon.move_(self.move_(to_id(move_))?);
+        }
+        pokemon.evs = set.evs;
+        pokemon.ivs = set.ivs;
+        pokemon.stat_exp = set.stat_exp;
+        pokemon.avs = set.avs;
+        pokemon.gender = set.gender;
+        pokemon.shiny = set.shiny;
+        pokemon.tera_type = set.tera_type;
+        pokemon.gigantamax = set.gigantamax;
+        Ok(TeamMember {
+            nickname: set.nickname.clone(),
+            pokeball: set.pokeball.clone(),
+            hidden_power_type: set.hidden_power_type,
+            dynamax_level: set.dynamax_level,
+            ..TeamMember::new(pokemon)
+        })
+    }
+    /// Looks up a set, keeping only what the damage calc uses.
+    pub fn resolve_pokemon(&self, set: &PokemonSet) -> Result<Pokemon<'_>, DexError> {
+        Ok(self.resolve_set(set)?.pokemon)
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "real_data")]
+mod tests {
+    use crate::{dex::{Dex, DexError}, generation::Generation, paste::TeamMember, species::Stat, types::Type};
+
+    use super::PokemonSet;
+
+    #[test]
+    fn pokemon_set_tests() -> Result<(), DexError> {
+        let dex = Dex::default();
+        let json = r#"[{
+            "name": "Flutter", "species": "Garchomp", "item": "Choice Scarf", "ability": "Rough Skin",
+            "moves": ["Earthquake", "Outrage"], "nature": "Jolly", "evs": {"atk": 252, "spd": 4, "spe": 252}, "teraType": "Ground"
+        }]"#;
+        let library: Vec<PokemonSet> = serde_json::from_str(json).unwrap();
+        let garchomp = dex.resolve_set(&library[0])?;
+        assert_eq!(garchomp.nickname.as_deref(), Some("Flutter"));
+        assert_eq!(garchomp.pokemon.level, 100);
+        assert_eq!(garchomp.pokemon.ivs.get(Stat::Speed), 31);
+        assert_eq!(garchomp.pokemon.stat(Stat::Speed), 333);
+        assert_eq!(garchomp.pokemon.tera_type, Some(Type::Ground));
+
+        // Sets saved from a team member store IDs, and survive a round trip through JSON
+        let set = PokemonSet::from(&garchomp);
+        assert_eq!((set.species.as_str(), set.item.as_deref()), ("garchomp", Some("choicescarf")));
+        let reloaded: PokemonSet = serde_json::from_str(&serde_json::to_string(&set).unwrap()).unwrap();
+        assert_eq!(reloaded, set);
+        assert_eq!(dex.resolve_pokemon(&reloaded)?.stat(Stat::Attack), garchomp.pokemon.stat(Stat::Attack));
+
+        // The same set can be resolved in another generation
+        let gen_4 = Dex::generation_dex(Generation::Four);
+        assert_eq!(gen_4.resolve_pokemon(&set)?.gen, Generation::Four);
+        assert!(dex.resolve_set(&PokemonSet::new("notapokemon")).is_err());
+
+        // Stat Experience and Awakening Values are kept too
+        let gen_2 = Dex::generation_dex(Generation::Two);
+        let snorlax = gen_2.pokemon("snorlax")?.level(100).stat_exps([u16::MAX; 6]);
+        let set = PokemonSet::from(&TeamMember::new(snorlax));
+        let reloaded: PokemonSet = serde_json::from_str(&serde_json::to_string(&set).unwrap()).unwrap();
+        assert_eq!(reloaded.stat_exp, [u16::MAX; 6].into());
+        assert_eq!(gen_2.resolve_pokemon(&reloaded)?.stat(Stat::HP), snorlax.stat(Stat::HP));
+
+        let lets_go = Dex::lets_go_dex();
+        let pikachu = lets_go.pokemon("pikachustarter")?.avs([200; 6]);
+        let set: PokemonSet = serde_json::from_str(&serde_json::to_string(&PokemonSet::from(&TeamMember::new(pikachu))).unwrap()).unwrap();
+        assert_eq!(set.avs, [200; 6].into());
+        assert_eq!(lets_go.resolve_pokemon(&set)?.stat(Stat::Speed), pikachu.stat(Stat::Speed));
+        Ok(())
+    }
+}
@@ -1,4 +1,4 @@
-use std::{collections::HashMap, marker::PhantomData};
+use std::{collections::HashMap, marker::PhantomData, sync::OnceLock};
 
 use serde::{de::DeserializeOwned, Deserialize};
 use serde_json::{Map, Value};
@@ -11,6 +11,7 @@ const LEARNSETS_JSON: &str = include_str!("../data/learnsets.json");
 const TYPES_JSON: &str = include_str!("../data/types.json");
 const NATURES_JSON: &str = include_str!("../data/natures.json");
 const ITEMS_JSON: &str = include_str!("../data/items.json");
+const ABILITIES_JSON: &str = include_str!("../data/abilities.json");
 
 trait Dexable {
     fn set_future(&mut self, gen: Generation); // Usually just check if this is a Future move.
@@ -24,13 +25,27 @@ impl Default for Dex {
 }
 impl Dex {
     pub fn generation_dex(gen: Generation) -> Self {
-        Self::new(gen_data(gen), gen_data(gen), gen_data(gen), gen_data(gen), gen_data(gen), gen_data(gen))
+        Self::new(gen, gen_data(gen), gen_data(gen), gen_data(gen), gen_data(gen), gen_data(gen), gen_data(gen))
     }
+    pub fn lets_go_dex() -> Self {
+        Self::generation_dex(Generation::Seven).into_lets_go()
+    }
+}
+
+/// The display name of every ability by its identifier, such as "mindseye" -> "Mind's Eye".
+pub(crate) fn ability_names() -> &'static HashMap<String, String> {
+    static NAMES: OnceLock<HashMap<String, String>> = OnceLock::new();
+    NAMES.get_or_init(|| {
+        let raw_data: HashMap<Generation, HashMap<String, Map<String, Value>>> = serde_json::from_str(ABILITIES_JSON).unwrap();
+        raw_data.into_values().flatten()
+            .filter_map(|(id, data)| Some((id, data.get("name")?.as_str()?.to_string())))
+            .collect()
+    })
 }
 
 fn gen_data<T: Dexable + DeserializeOwned>(gen: Generation) -> HashMap<String, T> {
     let raw_data = RawData::<T>::default();
-    raw_data.0.get(&LATEST_GENERATION).unwrap().iter().map(|(name, _)|
+    raw_data.0.get(&LATEST_GENERATION).unwrap().keys().map(|name|
         (name.clone(), raw_data.get(gen, name).unwrap())
     ).collect()
 }
@@ -57,6 +72,8 @@ impl<T:Dexable + DeserializeOwned> RawData<T> {
             Map::new()
         };
         let mut next = self.0.get(&gen).and_then(|map| map.get(identifier).cloned()).unwrap_or_default();
+        // Showdown marks entries that build on the next generation's entry, which is what this does for every entry.
+        next.remove("inherit");
         result.append(&mut next);
         result
     }
@@ -0,0 +1,188 @@
+use crate::{field::{Field, Side}, generation::Generation, moves::{SideCondition, Status, Terrain, VolatileStatus, Weather}, names::abilities::Ability, pokemon::Pokemon, types::Type};
+
+/// What caused an end of turn HP change.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResidualSource {
+    Weather(Weather),
+    Terrain(Terrain),
+    SideCondition(SideCondition),
+    Status(Status),
+    Volatile(VolatileStatus),
+    Ability(Ability),
+    /// The name of the item.
+    Item(String),
+}
+
+/// A single end of turn HP change. Negative for damage.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResidualEffect {
+    pub source: ResidualSource,
+    pub hp_change: i32,
+}
+
+/// Every end of turn HP change for a pokemon, in the order they happen.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Residual(pub Vec<ResidualEffect>);
+impl Residual {
+    pub fn total(&self) -> i32 {
+        self.0.iter().map(|effect| effect.hp_change).sum()
+    }
+    pub fn effects(&self) -> &[ResidualEffect] {
+        &self.0
+    }
+}
+
+/// Residual damage and healing are a fraction of max HP, rounded down but at least 1.
+pub(crate) fn fraction(max_hp: u16, numerator: u16, denominator: u16) -> i32 {
+    ((max_hp as u32 * numerator as u32 / denominator as u32).max(1)) as i32
+}
+
+impl Pokemon<'_> {
+    /// The HP this pokemon gains and loses at the end of the turn, on the given side of the field.
+    pub fn residual(&self, field: &Field, side: &Side) -> Residual {
+        let mut residual = Residual::default();
+        let max_hp = self.max_hp();
+        let item = self.active_item(field).map(|item| item.id());
+        let item = item.as_deref();
+        let has_type = |type_: Type| self.species.types.contains(&type_);
+        let magic_guard = self.ability == Ability::MagicGuard;
+        let heal_block = self.volatiles.contains(VolatileStatus::HealBlock);
+        let gen = self.gen;
+
+        let damage = |source: ResidualSource, numerator: u16, denominator: u16| {
+            (!magic_guard).then(|| ResidualEffect { source, hp_change: -fraction(max_hp, numerator, denominator) })
+        };
+        let heal = |source: ResidualSource, numerator: u16, denominator: u16| {
+            (!heal_block).then(|| ResidualEffect { source, hp_change: fraction(max_hp, numerator, denominator) })
+        };
+
+        // --- Weather ---
+        let weather = field.active_weather().filter(|_| ![Ability::AirLock, Ability::CloudNine].contains(&self.ability));
+        let umbrella = item == Some("utilityumbrella");
+        let safety_goggles = item == Some("safetygoggles");
+        match weather {
+            Some(Weather::Sandstorm) => {
+                let immune = has_type(Type::Rock) || has_type(Type::Ground) || has_type(Type::Steel) || safety_goggles
+                    || [Ability::SandVeil, Ability::SandRush, Ability::SandForce, Ability::Overcoat].contains(&self.ability);
+                if !immune {residual.0.extend(damage(ResidualSource::Weather(Weather::Sandstorm), 1, 16))}
+            },
+            Some(Weather::Hail) => {
+                let immune = has_type(Type::Ice) || safety_goggles
+                    || [Ability::IceBody, Ability::SnowCloak, Ability::Overcoat].contains(&self.ability);
+                if !immune {residual.0.extend(damage(ResidualSource::Weather(Weather::Hail), 1, 16))}
+            },
+            _ => {}
+        }
+        match (weather, self.ability) {
+            (Some(Weather::RainDance), Ability::RainDish) if !umbrella => residual.0.extend(heal(ResidualSource::Ability(Ability::RainDish), 1, 16)),
+            (Some(Weather::RainDance), Ability::DrySkin) if !umbrella => residual.0.extend(heal(ResidualSource::Ability(Ability::DrySkin), 1, 8)),
+            (Some(Weather::SunnyDay), Ability::DrySkin) if !umbrella => residual.0.extend(damage(ResidualSource::Ability(Ability::DrySkin), 1, 8)),
+            (Some(Weather::SunnyDay), Ability::SolarPower) if !umbrella => residual.0.extend(damage(ResidualSource::Ability(Ability::SolarPower), 1, 8)),
+            (Some(Weather::Hail | Weather::Snow), Ability::IceBody) => residual.0.extend(heal(ResidualSource::Ability(Ability::IceBody), 1, 16)),
+            _ => {}
+        }
+
+        // --- Side Conditions ---
+        // The Sea of Fire and G-Max moves damage every pokemon on the side that isn't of their type
+        let side_damage = [
+            (SideCondition::FirePledge, Type::Fire, 8),
+            (SideCondition::GMaxWildfire, Type::Fire, 6),
+            (SideCondition::GMaxCannonade, Type::Water, 6),
+            (SideCondition::GMaxVineLash, Type::Grass, 6),
+            (SideCondition::GMaxVolcalith, Type::Rock, 6),
+        ];
+        for (condition, immune_type, denominator) in side_damage {
+            if side.has_condition(condition) && !has_type(immune_type) {
+                residual.0.extend(damage(ResidualSource::SideCondition(condition), 1, denominator));
+            }
+        }
+
+        // --- Terrain ---
+        if field.terrain == Some(Terrain::Grassy) && self.is_grounded(field) {
+            residual.0.extend(heal(ResidualSource::Terrain(Terrain::Grassy), 1, 16));
+        }
+
+        // --- Items ---
+        match item {
+            Some("leftovers") => residual.0.extend(heal(ResidualSource::Item("Leftovers".to_string()), 1, 16)),
+            Some("blacksludge") if has_type(Type::Poison) => residual.0.extend(heal(ResidualSource::Item("Black Sludge".to_string()), 1, 16)),
+            Some("blacksludge") => residual.0.extend(damage(ResidualSource::Item("Black Sludge".to_string()), 1, 8)),
+            _ => {}
+        }
+
+        // --- Volatiles ---
+        if self.volatiles.contains(VolatileStatus::LeechSeed) {
+            let denominator = if gen == Generation::One {16} else {8};
+            residual.0.extend(damage(ResidualSource::Volatile(VolatileStatus::LeechSeed), 1, denominator));
+        }
+
+        // --- Status ---
+        match (self.status, self.ability) {
+            (Some(status @ (Status::Poison | Status::Toxic)), Ability::PoisonHeal) => residual.0.extend(heal(ResidualSource::Status(status), 1, 8)),
+            (Some(Status::Poison), _) => {
+                let denominator = if gen == Generation::One {16} else {8};
+                residual.0.extend(damage(ResidualSource::Status(Status::Poison), 1, denominator));
+            },
+            (Some(Status::Toxic), _) => {
+                let numerator = (self.toxic_turns as u16 + 1).min(15);
+                residual.0.extend(damage(ResidualSource::Status(Status::Toxic), numerator, 16));
+            },
+            (Some(Status::Burn), ability) => {
+                let denominator = if (Generation::Two..=Generation::Six).contains(&gen) {8} else {16};
+                let denominator = if ability == Ability::Heatproof {denominator * 2} else {denominator};
+                residual.0.extend(damage(ResidualSource::Status(Status::Burn), 1, denominator));
+            },
+            _ => {}
+        }
+        if self.volatiles.contains(VolatileStatus::Nightmare) && self.status == Some(Status::Sleep) {
+            residual.0.extend(damage(ResidualSource::Volatile(VolatileStatus::Nightmare), 1, 4));
+        }
+        if self.volatiles.contains(VolatileStatus::Curse) {
+            residual.0.extend(damage(ResidualSource::Volatile(VolatileStatus::Curse), 1, 4));
+        }
+        if self.volatiles.contains(VolatileStatus::PartiallyTrapped) {
+            let denominator = if gen >= Generation::Six {8} else {16};
+            residual.0.extend(damage(ResidualSource::Volatile(VolatileStatus::PartiallyTrapped), 1, denominator));
+        }
+        if self.volatiles.contains(VolatileStatus::SaltCure) {
+            let denominator = if has_type(Type::Water) || has_type(Type::Steel) {4} else {8};
+            residual.0.extend(damage(ResidualSource::Volatile(VolatileStatus::SaltCure), 1, denominator));
+        }
+
+        residual
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "real_data")]
+mod tests {
+    use crate::{dex::{Dex, DexError}, field::{Field, Side}, moves::{SideCondition, Status, VolatileStatus, Weather}, names::abilities::Ability};
+
+    #[test]
+    fn residual_tests() -> Result<(), DexError> {
+        let dex = Dex::default();
+        let sand = Field::new().weather(Weather::Sandstorm);
+        let side = Side::new();
+
+        let garchomp = dex.pokemon("garchomp")?;
+        assert_eq!(garchomp.residual(&sand, &side).total(), 0);
+
+        let dragonite = dex.pokemon("dragonite")?.item(dex.item("leftovers")?).status(Status::Toxic).toxic_turns(2);
+        let max_hp = dragonite.max_hp() as i32;
+        assert_eq!(dragonite.residual(&sand, &side).total(), -(max_hp / 16) + max_hp / 16 - (max_hp * 3 / 16));
+        // Air Lock on any pokemon on the field stops the sandstorm
+        assert_eq!(dragonite.residual(&sand.air_lock(true), &side).total(), max_hp / 16 - (max_hp * 3 / 16));
+
+        // The Sea of Fire doesn't hurt Fire types
+        let sea_of_fire = Side::new().condition(SideCondition::FirePledge);
+        assert_eq!(garchomp.residual(&Field::new(), &sea_of_fire).total(), -(garchomp.max_hp() as i32 / 8));
+        assert_eq!(dex.pokemon("arcanine")?.residual(&Field::new(), &sea_of_fire).total(), 0);
+
+        let gliscor = dex.pokemon("gliscor")?.ability(Ability::PoisonHeal).status(Status::Toxic);
+        assert_eq!(gliscor.residual(&Field::new(), &side).total(), gliscor.max_hp() as i32 / 8);
+
+        let dondozo = dex.pokemon("dondozo")?.volatile(VolatileStatus::SaltCure);
+        assert_eq!(dondozo.residual(&Field::new(), &side).total(), -(dondozo.max_hp() as i32 / 4));
+        Ok(())
+    }
+}
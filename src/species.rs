@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 
-use serde::{Deserialize, Deserializer};
+use serde::{Deserialize, Deserializer, Serialize};
 use serde_json::Value;
 
 use crate::{generation::Generation, learnsets::Gender, moves::NonStandardReason, names::abilities::Ability, types::Type};
@@ -58,7 +58,7 @@ pub struct Species {
     #[serde(default)]
     pub cannot_dynamax: bool,
     pub force_tera_type: Option<Type>,
-    #[serde(default)]
+    #[serde(default, rename = "unreleasedHidden")]
     pub unrelease_hidden: bool,
     #[serde(default)]
     pub male_only_hidden: bool,
@@ -79,13 +79,19 @@ fn deserialize_battle_only<'de, D>(deserializer: D) -> Result<Option<Vec<String>
 #[serde(from = "HashMap<char, Ability>")]
 pub struct Abilities {
     pub normal: Vec<Ability>,
-    pub hidden: Option<Ability>
+    pub hidden: Option<Ability>,
+    /// An ability only given out by special events. See Battle Bond.
+    pub special: Option<Ability>,
 }
 impl From<HashMap<char, Ability>> for Abilities {
     fn from(mut value: HashMap<char, Ability>) -> Self {
-        let hidden= value.remove(&'h');
-        let normal = value.into_iter().map(|(_, ability)| ability).collect();
-        Abilities { normal, hidden }
+        let hidden = value.remove(&'H');
+        let special = value.remove(&'S');
+        // Slots 0 and 1
+        let mut normal = value.into_iter().collect::<Vec<_>>();
+        normal.sort_by_key(|(slot, _)| *slot);
+        let normal = normal.into_iter().map(|(_, ability)| ability).collect();
+        Abilities { normal, hidden, special }
     }
 }
 
@@ -96,24 +102,25 @@ pub struct GenderRatio {
     pub m: f32
 }
 
-#[derive(Deserialize, Default, PartialEq, Eq, Debug, Clone, Copy)]
+/// A value for each stat. `u8` for base stats, IVs and EVs, `u16` for Stat Experience.
+#[derive(Serialize, Deserialize, Default, PartialEq, Eq, Debug, Clone, Copy)]
 #[serde(deny_unknown_fields)]
-pub struct StatDistribution {
+pub struct StatDistribution<T = u8> {
     #[serde(default)]
-    pub hp: u8,
+    pub hp: T,
     #[serde(default, rename = "atk")]
-    pub attack: u8,
+    pub attack: T,
     #[serde(default, rename = "def")]
-    pub defence: u8,
+    pub defence: T,
     #[serde(default, rename = "spa")]
-    pub special_attack: u8,
+    pub special_attack: T,
     #[serde(default, rename = "spd")]
-    pub special_defence: u8,
+    pub special_defence: T,
     #[serde(default, rename = "spe")]
-    pub speed: u8,
+    pub speed: T,
 }
-impl StatDistribution {
-    pub fn get(&self, stat: Stat) -> u8 {
+impl<T: Copy> StatDistribution<T> {
+    pub fn get(&self, stat: Stat) -> T {
         match stat {
             Stat::HP => self.hp,
             Stat::Attack => self.attack,
@@ -124,7 +131,7 @@ impl StatDistribution {
             _ => todo!()
         }
     }
-    pub fn get_mut(&mut self, stat: Stat) -> &mut u8 {
+    pub fn get_mut(&mut self, stat: Stat) -> &mut T {
         match stat {
             Stat::HP => &mut self.hp,
             Stat::Attack => &mut self.attack,
@@ -136,8 +143,8 @@ impl StatDistribution {
         }
     }
 }
-impl From<[u8;6]> for StatDistribution {
-    fn from(value: [u8;6]) -> Self {
+impl<T: Copy> From<[T;6]> for StatDistribution<T> {
+    fn from(value: [T;6]) -> Self {
         Self { hp: value[0], attack: value[1], defence: value[2], special_attack: value[3], special_defence: value[4], speed: value[5] }
     }
 }
@@ -0,0 +1,244 @@
+use std::fmt::Display;
+
+use crate::{dex::Dex, generation::Generation, items::ItemData, moves::MoveData, names::abilities::Ability, natures::NatureData, paste::{to_id, PasteError, TeamMember}, pokemon::Pokemon, species::{Species, StatDistribution}, types::Type};
+
+/// A pokemon as an Open Team Sheet shows it, which leaves out its EVs, IVs and nature.
+#[derive(Clone)]
+pub struct SheetPokemon<'a> {
+    pub species: &'a Species,
+    pub gen: Generation,
+    pub item: Option<&'a ItemData>,
+    pub ability: Ability,
+    pub moves: Vec<&'a MoveData>,
+    pub level: u8,
+    pub tera_type: Option<Type>,
+}
+impl<'a> From<&TeamMember<'a>> for SheetPokemon<'a> {
+    fn from(member: &TeamMember<'a>) -> Self {
+        let pokemon = &member.pokemon;
+        Self { species: pokemon.species, gen: pokemon.gen, item: pokemon.item, ability: pokemon.ability, moves: pokemon.moves().map(|slot| slot.move_).collect(), level: pokemon.level, tera_type: pokemon.tera_type }
+    }
+}
+impl<'a> SheetPokemon<'a> {
+    /// This pokemon with a guessed spread.
+    pub fn team_member(&self, spread: &Spread<'a>) -> TeamMember<'a> {
+        let mut pokemon = Pokemon::new(self.species, self.gen).level(self.level).ability(self.ability);
+        if let Some(item) = self.item {
+            pokemon = pokemon.item(item);
+        }
+        if let Some(nature) = spread.nature {
+            pokemon = pokemon.nature(nature);
+        }
+        if let Some(tera_type) = self.tera_type {
+            pokemon = pokemon.tera_type(tera_type);
+        }
+        pokemon = self.moves.iter().fold(pokemon, |pokemon, move_| pokemon.move_(move_));
+        pokemon.evs = spread.evs;
+        pokemon.ivs = spread.ivs;
+        TeamMember::new(pokemon)
+    }
+}
+
+/// The EVs, IVs and nature to assume for a pokemon from a team sheet.\
+/// The default is no EVs, perfect IVs and a neutral nature.
+#[derive(Clone, Copy)]
+pub struct Spread<'a> {
+    pub evs: StatDistribution,
+    pub ivs: StatDistribution,
+    pub nature: Option<&'a NatureData>,
+}
+impl Default for Spread<'_> {
+    fn default() -> Self {
+        Self { evs: StatDistribution::default(), ivs: [31, 31, 31, 31, 31, 31].into(), nature: None }
+    }
+}
+impl<'a> Spread<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn evs(mut self, evs: StatDistribution) -> Self {
+        self.evs = evs;
+        self
+    }
+    pub fn ivs(mut self, ivs: StatDistribution) -> Self {
+        self.ivs = ivs;
+        self
+    }
+    pub fn nature(mut self, nature: &'a NatureData) -> Self {
+        self.nature = Some(nature);
+        self
+    }
+}
+
+/// Something on a team sheet that isn't allowed by a [Dex], or by the usual VGC rules.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SheetProblem {
+    /// A species, item or move that isn't in the Dex's generation.
+    NotInGeneration(String),
+    /// An ability the species can't have.
+    IllegalAbility { species: String, ability: Ability },
+    /// A pokemon without a tera type in Gen 9, or with one before.
+    TeraType(String),
+    DuplicateSpecies(String),
+    DuplicateItem(String),
+}
+impl Display for SheetProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotInGeneration(name) => write!(f, "{name} is not available in this generation"),
+            Self::IllegalAbility { species, ability } => write!(f, "{species} can't have {}", ability.name()),
+            Self::TeraType(species) => write!(f, "{species} has a missing or unexpected tera type"),
+            Self::DuplicateSpecies(species) => write!(f, "{species} is on the team more than once"),
+            Self::DuplicateItem(item) => write!(f, "{item} is held more than once"),
+        }
+    }
+}
+
+/// An Open Team Sheet, as published for VGC tournaments.
+#[derive(Clone, Default)]
+pub struct TeamSheet<'a>(pub Vec<SheetPokemon<'a>>);
+impl<'a> From<&[TeamMember<'a>]> for TeamSheet<'a> {
+    fn from(team: &[TeamMember<'a>]) -> Self {
+        Self(team.iter().map(SheetPokemon::from).collect())
+    }
+}
+impl<'a> TeamSheet<'a> {
+    /// The full team, guessing the same spread for every pokemon.
+    pub fn team(&self, spread: &Spread<'a>) -> Vec<TeamMember<'a>> {
+        self.team_with(|_| *spread)
+    }
+    /// The full team, guessing a spread for each pokemon.
+    pub fn team_with(&self, spread: impl Fn(&SheetPokemon<'a>) -> Spread<'a>) -> Vec<TeamMember<'a>> {
+        self.0.iter().map(|pokemon| pokemon.team_member(&spread(pokemon))).collect()
+    }
+
+    /// Everything on this sheet that isn't available in the given Dex, and any broken species or item clauses.
+    pub fn validate(&self, dex: &Dex) -> Vec<SheetProblem> {
+        let mut problems = vec![];
+        let mut species_seen = vec![];
+        let mut items_seen = vec![];
+        for pokemon in self.0.iter() {
+            let species = &pokemon.species.name;
+            if dex.species(to_id(species)).map_or(true, |species| dex.nonstandard_reason(species.is_nonstandard).is_some()) {
+                problems.push(SheetProblem::NotInGeneration(species.clone()));
+            }
+            for move_ in pokemon.moves.iter() {
+                if dex.move_(to_id(&move_.name)).map_or(true, |move_| dex.nonstandard_reason(move_.is_nonstandard).is_some()) {
+                    problems.push(SheetProblem::NotInGeneration(move_.name.clone()));
+                }
+            }
+            let abilities = &pokemon.species.abilities;
+            if !abilities.normal.contains(&pokemon.ability) && ![abilities.hidden, abilities.special].contains(&Some(pokemon.ability)) {
+                problems.push(SheetProblem::IllegalAbility { species: species.clone(), ability: pokemon.ability });
+            }
+            if pokemon.tera_type.is_some() != (dex.generation() == Generation::Nine) {
+                problems.push(SheetProblem::TeraType(species.clone()));
+            }
+
+            let base_species = pokemon.species.base_species.as_ref().unwrap_or(species);
+            if species_seen.contains(&base_species) {
+                problems.push(SheetProblem::DuplicateSpecies(base_species.clone()));
+            }
+            species_seen.push(base_species);
+            if let Some(item) = pokemon.item {
+                if dex.item(item.id()).map_or(true, |item| dex.nonstandard_reason(item.is_nonstandard).is_some()) {
+                    problems.push(SheetProblem::NotInGeneration(item.name.clone()));
+                }
+                if items_seen.contains(&&item.name) {
+                    problems.push(SheetProblem::DuplicateItem(item.name.clone()));
+                }
+                items_seen.push(&item.name);
+            }
+        }
+        problems
+    }
+}
+
+impl Display for TeamSheet<'_> {
+    /// Writes this team sheet in the same layout as a Showdown paste, without the spreads.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, pokemon) in self.0.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", pokemon.species.name)?;
+            if let Some(item) = pokemon.item {
+                write!(f, " @ {}", item.name)?;
+            }
+            writeln!(f)?;
+            writeln!(f, "Ability: {}", pokemon.ability.name())?;
+            writeln!(f, "Level: {}", pokemon.level)?;
+            if let Some(tera_type) = pokemon.tera_type {
+                writeln!(f, "Tera Type: {tera_type:?}")?;
+            }
+            for move_ in pokemon.moves.iter() {
+                writeln!(f, "- {}", move_.name)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Dex {
+    /// Reads a team sheet, which uses the same layout as a Showdown paste.\
+    /// Lines that a team sheet doesn't show, like EVs and nicknames, are read but left out.
+    pub fn import_team_sheet(&self, text: &str) -> Result<TeamSheet<'_>, PasteError> {
+        Ok(TeamSheet::from(&self.import_team(text)?[..]))
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "real_data")]
+mod tests {
+    use crate::{dex::Dex, names::abilities::Ability, species::Stat, types::Type};
+
+    use super::{SheetProblem, Spread};
+
+    const SHEET: &str = "Flutter Mane @ Booster Energy
+Ability: Protosynthesis
+Level: 50
+Tera Type: Fairy
+- Moonblast
+- Shadow Ball
+- Icy Wind
+- Protect
+
+Incineroar @ Safety Goggles
+Ability: Intimidate
+Level: 50
+Tera Type: Ghost
+- Fake Out
+- Flare Blitz
+- Knock Off
+- Parting Shot
+";
+
+    #[test]
+    fn team_sheet_tests() {
+        let dex = Dex::default();
+        let sheet = dex.import_team_sheet(SHEET).unwrap();
+        assert_eq!(sheet.0.len(), 2);
+        assert_eq!(sheet.0[1].ability, Ability::Intimidate);
+        assert_eq!(sheet.0[0].tera_type, Some(Type::Fairy));
+        assert_eq!(sheet.to_string(), SHEET);
+        assert_eq!(sheet.validate(&dex), vec![]);
+
+        let timid = dex.nature("timid").unwrap();
+        let spread = Spread::new().evs([4, 0, 0, 252, 0, 252].into()).nature(timid);
+        let team = sheet.team(&spread);
+        assert_eq!(team[0].pokemon.evs.get(Stat::Speed), 252);
+        assert_eq!(team[1].pokemon.ivs.get(Stat::Attack), 31);
+        assert_eq!(team[1].pokemon.moves().count(), 4);
+
+        // Species and item clauses, and abilities the species can't have
+        let mut duplicated = sheet.team(&spread);
+        duplicated.push(duplicated[1].clone());
+        duplicated[0].pokemon.ability = Ability::Intimidate;
+        let problems = super::TeamSheet::from(&duplicated[..]).validate(&dex);
+        assert_eq!(problems, vec![
+            SheetProblem::IllegalAbility { species: "Flutter Mane".to_string(), ability: Ability::Intimidate },
+            SheetProblem::DuplicateSpecies("Incineroar".to_string()),
+            SheetProblem::DuplicateItem("Safety Goggles".to_string()),
+        ]);
+    }
+}
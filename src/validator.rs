@@ -0,0 +1,301 @@
+use std::fmt::Display;
+
+use crate::{dex::Dex, generation::Generation, hidden_power::HiddenPowerMismatch, learnsets::Gender, moves::NonStandardReason, names::abilities::Ability, paste::{to_id, TeamMember, MAX_TEAM_SIZE}, pokemon::{iv_to_dv, GenderMismatch, Pokemon}, species::Stat};
+
+pub const MAX_EV_TOTAL: u16 = 510;
+pub const MAX_EV: u8 = 252;
+pub const MAX_IV: u8 = 31;
+/// Gen 1-2 DVs, which are stored as IVs. See [iv_to_dv].
+pub const MAX_DV: u8 = 15;
+/// Let's Go AVs, which take the place of EVs.
+pub const MAX_AV: u8 = 200;
+pub const MAX_LEVEL: u8 = 100;
+
+const STATS: [Stat; 6] = [Stat::HP, Stat::Attack, Stat::Defence, Stat::SpecialAttack, Stat::SpecialDefence, Stat::Speed];
+
+/// Why a pokemon isn't allowed on a team.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Problem {
+    TooManyPokemon,
+    EvTotal(u16),
+    Ev { stat: Stat, ev: u8 },
+    Iv { stat: Stat, iv: u8 },
+    Dv { stat: Stat, dv: u8 },
+    /// Special Attack and Special Defence DVs that differ, though Gen 1-2 only have one Special DV.
+    SpecialDv { special_attack: u8, special_defence: u8 },
+    Av { stat: Stat, av: u8 },
+    Level(u8),
+    /// An ability the species can't have in this generation.
+    Ability(Ability),
+    /// A hidden ability that hasn't been released yet.
+    UnreleasedHiddenAbility(Ability),
+    /// A hidden ability that is only available on male pokemon.
+    MaleOnlyHiddenAbility(Ability),
+    /// A move that isn't in the species' learnset for this generation.
+    CannotLearn(String),
+    /// A species, item or move that isn't available in this generation.
+    NonStandard { name: String, reason: NonStandardReason },
+    /// A forme that needs an item, ability or move it doesn't have. See Zacian-Crowned and Keldeo-Resolute.
+    RequiredItem(String),
+    RequiredAbility(String),
+    RequiredMove(String),
+    Gender(GenderMismatch),
+    HiddenPower(HiddenPowerMismatch),
+    /// A species that only comes from events, that doesn't match any of them. See [Dex::legal_encounters].
+    NoMatchingEvent,
+}
+impl Display for Problem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TooManyPokemon => write!(f, "There are more than {MAX_TEAM_SIZE} pokemon"),
+            Self::EvTotal(total) => write!(f, "EVs add up to {total}, more than {MAX_EV_TOTAL}"),
+            Self::Ev { stat, ev } => write!(f, "{ev} {stat:?} EVs is more than {MAX_EV}"),
+            Self::Iv { stat, iv } => write!(f, "{iv} {stat:?} IVs is more than {MAX_IV}"),
+            Self::Dv { stat, dv } => write!(f, "{dv} {stat:?} DVs is more than {MAX_DV}"),
+            Self::SpecialDv { special_attack, special_defence } => write!(f, "Special Attack and Special Defence DVs must match, but are {special_attack} and {special_defence}"),
+            Self::Av { stat, av } => write!(f, "{av} {stat:?} AVs is more than {MAX_AV}"),
+            Self::Level(level) => write!(f, "Level {level} is not between 1 and {MAX_LEVEL}"),
+            Self::Ability(ability) => write!(f, "Can't have {}", ability.name()),
+            Self::UnreleasedHiddenAbility(ability) => write!(f, "{} is an unreleased hidden ability", ability.name()),
+            Self::MaleOnlyHiddenAbility(ability) => write!(f, "{} is only available on males", ability.name()),
+            Self::CannotLearn(move_) => write!(f, "Can't learn {move_}"),
+            Self::NonStandard { name, reason } => write!(f, "{name} is not available ({reason:?})"),
+            Self::RequiredItem(item) => write!(f, "Must hold {item}"),
+            Self::RequiredAbility(ability) => write!(f, "Must have {ability}"),
+            Self::RequiredMove(move_) => write!(f, "Must know {move_}"),
+            Self::Gender(mismatch) => mismatch.fmt(f),
+            Self::HiddenPower(mismatch) => mismatch.fmt(f),
+            Self::NoMatchingEvent => write!(f, "Only comes from events, and doesn't match any of them"),
+        }
+    }
+}
+
+/// A problem with the pokemon at the given position in a team (from 0).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TeamProblem {
+    pub position: usize,
+    pub species: String,
+    pub problem: Problem,
+}
+impl Display for TeamProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (slot {}): {}", self.species, self.position + 1, self.problem)
+    }
+}
+
+impl Dex {
+    /// Every problem with a team in this Dex's generation.
+    pub fn validate_team(&self, team: &[TeamMember]) -> Vec<TeamProblem> {
+        team.iter().enumerate().flat_map(|(position, member)| {
+            let too_many = (position >= MAX_TEAM_SIZE).then_some(Problem::TooManyPokemon);
+            too_many.into_iter().chain(self.validate_pokemon(member)).map(move |problem| {
+                TeamProblem { position, species: member.pokemon.species.name.clone(), problem }
+            })
+        }).collect()
+    }
+
+    /// Every problem with a single pokemon in this Dex's generation.\
+    /// Moves are only checked for species with learnset data.
+    pub fn validate_pokemon(&self, member: &TeamMember) -> Vec<Problem> {
+        let pokemon = &member.pokemon;
+        let mut problems = vec![];
+        let mut non_standard = |name: &str, reason: Option<NonStandardReason>| if let Some(reason) = self.nonstandard_reason(reason) {
+            problems.push(Problem::NonStandard { name: name.to_string(), reason });
+        };
+        let species = self.species(to_id(&pokemon.species.name)).unwrap_or(pokemon.species);
+        non_standard(&species.name, species.is_nonstandard);
+        if let Some(item) = pokemon.item {
+            non_standard(&item.name, self.item(item.id()).map_or(item.is_nonstandard, |item| item.is_nonstandard));
+        }
+        for slot in pokemon.moves() {
+            let move_ = self.move_(to_id(&slot.move_.name)).unwrap_or(slot.move_);
+            non_standard(&move_.name, move_.is_nonstandard);
+        }
+
+        problems.extend(stat_problems(pokemon));
+        if !(1..=MAX_LEVEL).contains(&pokemon.level) {
+            problems.push(Problem::Level(pokemon.level));
+        }
+
+        problems.extend(self.ability_problem(pokemon));
+        problems.extend(self.learnset_problems(pokemon));
+        problems.extend(forme_problems(pokemon));
+        if let Err(mismatch) = pokemon.check_gender() {
+            problems.push(Problem::Gender(mismatch));
+        }
+        // Hyper Training lets Hidden Power's type be chosen freely, from level 100 in Gen 7-8 and level 50 in Gen 9
+        let hyper_training_level = match self.generation() {
+            Generation::Seven | Generation::Eight => Some(100),
+            Generation::Nine => Some(50),
+            _ => None,
+        };
+        let hyper_trained = hyper_training_level.is_some_and(|level| pokemon.level >= level);
+        if let Some(claimed) = member.hidden_power_type.filter(|_| !hyper_trained) {
+            if let Err(mismatch) = pokemon.check_hidden_power_type(claimed) {
+                problems.push(Problem::HiddenPower(mismatch));
+            }
+        }
+        if self.is_event_only(member) && self.legal_encounters(member).is_empty() {
+            problems.push(Problem::NoMatchingEvent);
+        }
+        problems
+    }
+
+    fn ability_problem(&self, pokemon: &Pokemon) -> Option<Problem> {
+        let gen = self.generation();
+        let ability = pokemon.ability;
+        let abilities = &pokemon.species.abilities;
+        // Neither Gen 1-2 nor Let's Go have abilities
+        if gen < Generation::Three || pokemon.lets_go {
+            return (ability != Ability::NoAbility).then_some(Problem::Ability(ability))
+        }
+        if abilities.normal.contains(&ability) || abilities.special == Some(ability) {
+            return None
+        }
+        if abilities.hidden != Some(ability) || gen < Generation::Five {
+            return Some(Problem::Ability(ability))
+        }
+        if pokemon.species.unrelease_hidden {
+            return Some(Problem::UnreleasedHiddenAbility(ability))
+        }
+        (pokemon.species.male_only_hidden && pokemon.gender.is_some_and(|gender| gender != Gender::Male))
+            .then_some(Problem::MaleOnlyHiddenAbility(ability))
+    }
+
+    /// Moves that aren't in the species' learnset, or a prevolution's. Nothing is checked if none of them have a learnset.
+    fn learnset_problems(&self, pokemon: &Pokemon) -> Vec<Problem> {
+        let learnset = self.resolved_learnset(pokemon.species);
+        if learnset.is_empty() {
+            return vec![]
+        }
+        pokemon.moves()
+            .filter(|slot| !slot.move_.id().is_some_and(|move_| learnset.can_learn(move_, self.generation())))
+            .map(|slot| Problem::CannotLearn(slot.move_.name.clone()))
+            .collect()
+    }
+}
+
+/// EVs and IVs from Gen 3 on, DVs in Gen 1-2 and AVs in Let's Go.
+/// Stat Experience needs no check, as it can be anything up to 65535.
+fn stat_problems(pokemon: &Pokemon) -> Vec<Problem> {
+    let mut problems = vec![];
+    if pokemon.gen < Generation::Three {
+        // The HP DV is made from the others, so only they are checked
+        for stat in STATS.into_iter().filter(|stat| *stat != Stat::HP) {
+            let dv = iv_to_dv(pokemon.ivs.get(stat));
+            if dv > MAX_DV {
+                problems.push(Problem::Dv { stat, dv });
+            }
+        }
+        let (special_attack, special_defence) = (iv_to_dv(pokemon.ivs.special_attack), iv_to_dv(pokemon.ivs.special_defence));
+        if special_attack != special_defence {
+            problems.push(Problem::SpecialDv { special_attack, special_defence });
+        }
+        return problems
+    }
+
+    if !pokemon.lets_go {
+        let ev_total = STATS.iter().map(|stat| pokemon.evs.get(*stat) as u16).sum::<u16>();
+        if ev_total > MAX_EV_TOTAL {
+            problems.push(Problem::EvTotal(ev_total));
+        }
+    }
+    for stat in STATS {
+        let (ev, iv, av) = (pokemon.evs.get(stat), pokemon.ivs.get(stat), pokemon.avs.get(stat));
+        if ev > MAX_EV && !pokemon.lets_go {
+            problems.push(Problem::Ev { stat, ev });
+        }
+        if iv > MAX_IV {
+            problems.push(Problem::Iv { stat, iv });
+        }
+        if av > MAX_AV && pokemon.lets_go {
+            problems.push(Problem::Av { stat, av });
+        }
+    }
+    problems
+}
+
+/// Problems with formes that need a certain item, ability or move.
+fn forme_problems(pokemon: &Pokemon) -> Vec<Problem> {
+    let species = pokemon.species;
+    let mut problems = vec![];
+    let item = pokemon.item.map(|item| item.id());
+    let required_items = species.required_item.iter().chain(species.required_items.iter()).collect::<Vec<_>>();
+    if !required_items.is_empty() && !required_items.iter().any(|required| Some(to_id(required)) == item) {
+        problems.push(Problem::RequiredItem(required_items[0].clone()));
+    }
+    if let Some(required) = &species.required_ability {
+        if to_id(required) != to_id(&pokemon.ability.name()) {
+            problems.push(Problem::RequiredAbility(required.clone()));
+        }
+    }
+    if let Some(required) = &species.required_move {
+        if !pokemon.moves().any(|slot| to_id(&slot.move_.name) == to_id(required)) {
+            problems.push(Problem::RequiredMove(required.clone()));
+        }
+    }
+    problems
+}
+
+#[cfg(test)]
+#[cfg(feature = "real_data")]
+mod tests {
+    use crate::{dex::{Dex, DexError}, generation::Generation, learnsets::Gender, moves::NonStandardReason, names::abilities::Ability, paste::TeamMember, species::Stat, types::Type};
+
+    use super::Problem;
+
+    #[test]
+    fn validator_tests() -> Result<(), DexError> {
+        let dex = Dex::default();
+        let garchomp = dex.pokemon("garchomp")?.level(100).evs([4, 252, 0, 0, 0, 252]).move_(dex.move_("earthquake")?);
+        let team = vec![TeamMember::new(garchomp)];
+        assert_eq!(dex.validate_team(&team), vec![]);
+
+        let broken = garchomp.ev(Stat::HP, 255).iv(Stat::Speed, 32).ability(Ability::Levitate).level(0);
+        assert_eq!(dex.validate_pokemon(&TeamMember::new(broken)), vec![
+            Problem::EvTotal(759),
+            Problem::Ev { stat: Stat::HP, ev: 255 },
+            Problem::Iv { stat: Stat::Speed, iv: 32 },
+            Problem::Level(0),
+            Problem::Ability(Ability::Levitate),
+        ]);
+
+        // Gen 1-2 have DVs and Stat Experience instead, with one Special DV, and Let's Go has AVs
+        let gen_2 = Dex::generation_dex(Generation::Two);
+        let snorlax = gen_2.pokemon("snorlax")?.level(100).stat_exps([u16::MAX; 6]).evs([255; 6]);
+        assert_eq!(gen_2.validate_pokemon(&TeamMember::new(snorlax)), vec![]);
+        assert_eq!(gen_2.validate_pokemon(&TeamMember::new(snorlax.dv(Stat::SpecialDefence, 14).iv(Stat::Speed, 33))), vec![
+            Problem::Dv { stat: Stat::Speed, dv: 16 },
+            Problem::SpecialDv { special_attack: 15, special_defence: 14 },
+        ]);
+        let lets_go = Dex::lets_go_dex();
+        let pikachu = lets_go.pokemon("pikachu")?.level(100).evs([255; 6]).avs([200; 6]);
+        assert_eq!(lets_go.validate_pokemon(&TeamMember::new(pikachu)), vec![]);
+        assert_eq!(lets_go.validate_pokemon(&TeamMember::new(pikachu.av(Stat::Speed, 201))), vec![Problem::Av { stat: Stat::Speed, av: 201 }]);
+
+        // Past species, items and moves
+        let problems = dex.validate_pokemon(&TeamMember::new(dex.pokemon("beedrill")?.item(dex.item("beedrillite")?)));
+        assert!(problems.contains(&Problem::NonStandard { name: "Beedrillite".to_string(), reason: NonStandardReason::Past }));
+
+        // Formes that need an item, and hidden abilities
+        let zacian = dex.pokemon("zaciancrowned")?;
+        assert_eq!(dex.validate_pokemon(&TeamMember::new(zacian)), vec![Problem::RequiredItem("Rusted Sword".to_string())]);
+        let rusted_sword = zacian.item(dex.item("rustedsword")?);
+        assert_eq!(dex.validate_pokemon(&TeamMember::new(rusted_sword)), vec![]);
+        let gen_4 = Dex::generation_dex(Generation::Four);
+        let hidden = gen_4.pokemon("garchomp")?.ability(Ability::RoughSkin);
+        assert_eq!(gen_4.validate_pokemon(&TeamMember::new(hidden)), vec![Problem::Ability(Ability::RoughSkin)]);
+
+        let gallade = dex.pokemon("gallade")?.gender(Gender::Female);
+        assert!(matches!(dex.validate_pokemon(&TeamMember::new(gallade))[..], [Problem::Gender(_)]));
+
+        // Hidden Power's type has to match the IVs unless it can be Hyper Trained
+        let gen_8 = Dex::generation_dex(Generation::Eight);
+        let fire = |pokemon| TeamMember { hidden_power_type: Some(Type::Fire), ..TeamMember::new(pokemon) };
+        let magnezone = gen_8.pokemon("magnezone")?;
+        assert!(matches!(gen_8.validate_pokemon(&fire(magnezone.level(99)))[..], [Problem::HiddenPower(_)]));
+        assert_eq!(gen_8.validate_pokemon(&fire(magnezone.level(100))), vec![]);
+        assert_eq!(dex.validate_pokemon(&fire(dex.pokemon("magnezone")?)), vec![]);
+        Ok(())
+    }
+}